@@ -302,6 +302,7 @@ impl<H: Handler, N: none::None, P: password::Password, PK: publickey::Publickey>
                         Attempt::Success => {
                             break if service_name == H::SERVICE_NAME {
                                 session.send(&userauth::Success).await?;
+                                session.set_authenticated();
 
                                 self.handler.on_request(session).await
                             } else {
@@ -171,6 +171,8 @@ impl<R: Request> Request for Auth<R> {
             let response = self.attempt_method(&mut session, &method).await?;
 
             if response.to::<userauth::Success>().is_ok() {
+                session.set_authenticated();
+
                 break self.service.on_accept(session).await;
             } else if let Ok(userauth::Failure { continue_with, .. }) = response.to() {
                 // TODO: (compliance) Take care of partial success
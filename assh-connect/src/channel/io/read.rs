@@ -48,7 +48,7 @@ impl<IO: Pipe, S: Side> futures::AsyncRead for Read<'_, IO, S> {
         )
         .entered();
 
-        if let Some(bytes_to_add) = self.channel.local_window.adjustable() {
+        if let Some(bytes_to_add) = self.channel.state.local_window.adjustable() {
             tracing::debug!(
                 "Adjusted window size by `{}` for channel #{}",
                 bytes_to_add,
@@ -68,7 +68,7 @@ impl<IO: Pipe, S: Side> futures::AsyncRead for Read<'_, IO, S> {
         match self.receiver.try_recv() {
             Ok(data) => {
                 self.buffer.extend(data.iter());
-                self.channel.local_window.consume(data.len() as u32);
+                self.channel.state.local_window.consume(data.len() as u32);
 
                 tracing::trace!(
                     "Received data block for stream `{:?}` on channel #{} of size `{}`",
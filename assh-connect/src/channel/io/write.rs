@@ -1,4 +1,4 @@
-use std::{io, num::NonZeroU32, pin::Pin, task};
+use std::{io, num::NonZeroU32, pin::Pin, sync::atomic::Ordering, task};
 
 use assh::{Pipe, side::Side};
 use ssh_packet::connect;
@@ -23,7 +23,13 @@ impl<'s, IO: Pipe, S: Side> Write<'s, IO, S> {
     }
 
     fn feed_data(&mut self) {
-        let data = std::mem::take(&mut self.buffer).into();
+        let data: Vec<u8> = std::mem::take(&mut self.buffer);
+        self.channel
+            .state
+            .tx
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
+
+        let data = data.into();
 
         match self.stream_id {
             Some(data_type) => self.channel.mux.feed(&connect::ChannelExtendedData {
@@ -65,8 +71,12 @@ impl<IO: Pipe, S: Side> futures::AsyncWrite for Write<'_, IO, S> {
             return task::Poll::Pending;
         }
 
-        let reserved =
-            futures::ready!(self.channel.remote_window.poll_reserve(cx, writable as u32)) as usize;
+        let reserved = futures::ready!(
+            self.channel
+                .state
+                .remote_window
+                .poll_reserve(cx, writable as u32)
+        ) as usize;
         self.buffer.extend_from_slice(&buf[..reserved]);
 
         task::Poll::Ready(Ok(reserved))
@@ -1,6 +1,10 @@
 //! Multiplexed I/O and requests on _channels_.
 
-use std::{num::NonZeroU32, task};
+use std::{
+    num::NonZeroU32,
+    sync::{Arc, atomic::Ordering},
+    task,
+};
 
 use assh::{Pipe, side::Side};
 use dashmap::DashMap;
@@ -20,6 +24,10 @@ pub(crate) use id::Id;
 mod window;
 pub(crate) use window::{LocalWindow, RemoteWindow};
 
+mod state;
+pub use state::Info;
+pub(crate) use state::State;
+
 pub mod request;
 
 /// A reference to an opened _channel_.
@@ -28,8 +36,7 @@ pub struct Channel<'s, IO: Pipe, S: Side> {
 
     id: Id,
 
-    local_window: LocalWindow,
-    remote_window: RemoteWindow,
+    state: Arc<State>,
     remote_maxpack: u32,
 
     streams: DashMap<Option<NonZeroU32>, flume::Sender<Vec<u8>>>,
@@ -43,6 +50,7 @@ where
     pub(crate) fn new(
         mux: &'s Mux<IO, S>,
         id: Id,
+        kind: String,
         remote_window: u32,
         remote_maxpack: u32,
     ) -> Self {
@@ -51,13 +59,23 @@ where
         mux.register(Interest::ChannelEof(id.local()));
         mux.register(Interest::ChannelWindowAdjust(id.local()));
 
+        let state = Arc::new(State {
+            local_id: id.local(),
+            remote_id: id.remote(),
+            kind,
+            local_window: Default::default(),
+            remote_window: RemoteWindow::from(remote_window),
+            rx: Default::default(),
+            tx: Default::default(),
+        });
+        mux.opened.insert(id.local(), state.clone());
+
         Self {
             mux,
 
             id,
 
-            local_window: Default::default(),
-            remote_window: RemoteWindow::from(remote_window),
+            state,
             remote_maxpack,
 
             streams: Default::default(),
@@ -110,6 +128,9 @@ where
                 Data::Plain(message) => (None, message.data.into_vec()),
                 Data::Extended(message) => (Some(message.data_type), message.data.into_vec()),
             };
+            self.state
+                .rx
+                .fetch_add(data.len() as u64, Ordering::Relaxed);
 
             match self.streams.get(&stream_id) {
                 Some(sender) => {
@@ -144,7 +165,7 @@ where
             )
         {
             let bytes_to_add = result?.bytes_to_add;
-            self.remote_window.replenish(bytes_to_add);
+            self.state.remote_window.replenish(bytes_to_add);
 
             tracing::debug!(
                 "Peer extended data window by `{}` bytes for channel #{}",
@@ -172,6 +193,11 @@ where
         self.mux.poll_interest(cx, interest)
     }
 
+    /// A snapshot of the state of the channel.
+    pub fn info(&self) -> Info {
+        self.state.info()
+    }
+
     /// Iterate over the incoming _channel requests_.
     pub fn requests(&self) -> impl TryStream<Ok = request::Request<'_, IO, S>, Error = Error> + '_ {
         let interest = Interest::ChannelRequest(self.id.local());
@@ -281,6 +307,7 @@ where
 impl<'s, IO: Pipe, S: Side> Drop for Channel<'s, IO, S> {
     fn drop(&mut self) {
         self.unregister_all();
+        self.mux.opened.remove(&self.id.local());
 
         tracing::debug!("Reporting channel #{} as closed", self.id.local());
 
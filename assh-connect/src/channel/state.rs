@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::{LocalWindow, RemoteWindow};
+
+/// The state of an opened _channel_, shared with the [`crate::Connect`] for reporting.
+pub struct State {
+    pub local_id: u32,
+    pub remote_id: u32,
+    pub kind: String,
+
+    pub local_window: LocalWindow,
+    pub remote_window: RemoteWindow,
+
+    pub rx: AtomicU64,
+    pub tx: AtomicU64,
+}
+
+impl State {
+    pub fn info(&self) -> Info {
+        Info {
+            local_id: self.local_id,
+            remote_id: self.remote_id,
+            kind: self.kind.clone(),
+            local_window: self.local_window.size(),
+            remote_window: self.remote_window.size(),
+            rx_bytes: self.rx.load(Ordering::Relaxed),
+            tx_bytes: self.tx.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of the state of an opened _channel_.
+#[derive(Debug, Clone)]
+pub struct Info {
+    /// Our identifier for the channel.
+    pub local_id: u32,
+
+    /// The peer's identifier for the channel.
+    pub remote_id: u32,
+
+    /// The type of the channel, such as `session` or `direct-tcpip`.
+    pub kind: String,
+
+    /// The remaining size of our window, in bytes the peer is allowed to send.
+    pub local_window: u32,
+
+    /// The remaining size of the peer's window, in bytes we are allowed to send.
+    pub remote_window: u32,
+
+    /// The number of bytes received in the channel, across all data streams.
+    pub rx_bytes: u64,
+
+    /// The number of bytes sent in the channel, across all data streams.
+    pub tx_bytes: u64,
+}
@@ -30,6 +30,10 @@ impl LocalWindow {
         previous.map(|previous| Self::INITIAL_WINDOW_SIZE - previous)
     }
 
+    pub fn size(&self) -> u32 {
+        self.inner.load(Ordering::SeqCst)
+    }
+
     pub fn consume(&self, size: u32) {
         let previous = self.inner.fetch_sub(size, Ordering::SeqCst);
 
@@ -60,6 +64,10 @@ pub struct RemoteWindow {
 }
 
 impl RemoteWindow {
+    pub fn size(&self) -> u32 {
+        self.inner.load(Ordering::SeqCst)
+    }
+
     pub fn replenish(&self, bytes_to_add: u32) {
         self.inner.fetch_add(bytes_to_add, Ordering::SeqCst);
         self.waker.wake();
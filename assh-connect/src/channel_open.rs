@@ -63,6 +63,7 @@ impl<'s, IO: Pipe, S: Side> ChannelOpen<'s, IO, S> {
         Ok(channel::Channel::new(
             self.mux,
             self.id.clone(),
+            inner.context.as_ascii().to_string(),
             inner.initial_window_size,
             inner.maximum_packet_size,
         ))
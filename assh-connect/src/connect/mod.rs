@@ -1,6 +1,9 @@
 //! Facilities to interract with the SSH _connect_ protocol.
 
-use assh::{Pipe, side::Side};
+use assh::{
+    Pipe,
+    side::{Side, server::Server},
+};
 use futures::{FutureExt, TryStream, task};
 use ssh_packet::{binrw, connect};
 
@@ -56,9 +59,12 @@ where
     // TODO: (ux) Compact `Self::global_request`, `Self::global_request_wait` with a trait ?
 
     /// Send a _global request_.
-    pub async fn global_request(&self, context: connect::GlobalRequestContext<'_>) -> Result<()> {
+    pub async fn global_request(
+        &self,
+        context: global_request::GlobalRequestContext<'_>,
+    ) -> Result<()> {
         self.mux
-            .send(&connect::GlobalRequest {
+            .send(&global_request::Message {
                 want_reply: false.into(),
                 context,
             })
@@ -70,15 +76,19 @@ where
     /// Send a _global request_, and wait for it's response.
     pub async fn global_request_wait(
         &self,
-        context: connect::GlobalRequestContext<'_>,
+        context: global_request::GlobalRequestContext<'_>,
     ) -> Result<global_request::Response> {
         let interest = Interest::GlobalResponse;
         let _unregister_on_drop = self.mux.register_scoped(interest);
 
-        let with_port = matches!(context, connect::GlobalRequestContext::TcpipForward { bind_port, .. } if bind_port == 0);
+        let with_port = matches!(context, global_request::GlobalRequestContext::TcpipForward { bind_port, .. } if bind_port == 0);
+        let with_proof = matches!(
+            context,
+            global_request::GlobalRequestContext::HostKeysProve { .. }
+        );
 
         self.mux
-            .send(&connect::GlobalRequest {
+            .send(&global_request::Message {
                 want_reply: true.into(),
                 context,
             })
@@ -98,15 +108,14 @@ where
             Failure(connect::RequestFailure),
         }
 
-        if !with_port {
-            futures::future::poll_fn(|cx| self.mux.poll_interest::<Response>(cx, &interest))
-                .map(|polled| match polled.transpose()? {
-                    Some(Response::Success(_)) => Ok(global_request::Response::Success(None)),
-                    Some(Response::Failure(_)) => Ok(global_request::Response::Failure),
-                    _ => Err(Error::ChannelClosed),
-                })
-                .await
-        } else {
+        #[binrw::binrw]
+        #[br(little)]
+        enum ResponseProof {
+            Success(global_request::HostKeysProof),
+            Failure(connect::RequestFailure),
+        }
+
+        if with_port {
             futures::future::poll_fn(|cx| self.mux.poll_interest::<ResponsePort>(cx, &interest))
                 .map(|polled| match polled.transpose()? {
                     Some(ResponsePort::Success(message)) => {
@@ -116,9 +125,49 @@ where
                     _ => Err(Error::SessionClosed),
                 })
                 .await
+        } else if with_proof {
+            futures::future::poll_fn(|cx| self.mux.poll_interest::<ResponseProof>(cx, &interest))
+                .map(|polled| match polled.transpose()? {
+                    Some(ResponseProof::Success(message)) => Ok(global_request::Response::Proof(
+                        message
+                            .signatures
+                            .into_iter()
+                            .map(|signature| signature.into_vec())
+                            .collect(),
+                    )),
+                    Some(ResponseProof::Failure(_)) => Ok(global_request::Response::Failure),
+                    _ => Err(Error::SessionClosed),
+                })
+                .await
+        } else {
+            futures::future::poll_fn(|cx| self.mux.poll_interest::<Response>(cx, &interest))
+                .map(|polled| match polled.transpose()? {
+                    Some(Response::Success(_)) => Ok(global_request::Response::Success(None)),
+                    Some(Response::Failure(_)) => Ok(global_request::Response::Failure),
+                    _ => Err(Error::ChannelClosed),
+                })
+                .await
         }
     }
 
+    /// Access initial exchange hash, binding the proofs of `hostkeys-prove-00@openssh.com`.
+    pub fn session_id(&self) -> Option<&[u8]> {
+        self.mux.session_id.as_deref()
+    }
+
+    /// A snapshot of the state of the currently opened _channels_, ordered by identifier.
+    pub fn channels(&self) -> Vec<channel::Info> {
+        let mut channels = self
+            .mux
+            .opened
+            .iter()
+            .map(|state| state.info())
+            .collect::<Vec<_>>();
+        channels.sort_by_key(|info| info.local_id);
+
+        channels
+    }
+
     /// Iterate over the incoming _channel open requests_.
     pub fn channel_opens(
         &self,
@@ -175,6 +224,7 @@ where
         let interest = Interest::ChannelOpenResponse(reserved.index() as u32);
         let _unregister_on_drop = self.mux.register_scoped(interest);
 
+        let kind = context.as_ascii().to_string();
         self.mux
             .send(&connect::ChannelOpen {
                 sender_channel: reserved.index() as u32,
@@ -199,6 +249,7 @@ where
                     Ok(channel_open::Response::Success(channel::Channel::new(
                         &self.mux,
                         id.into(),
+                        kind,
                         message.initial_window_size,
                         message.maximum_packet_size,
                     )))
@@ -213,6 +264,31 @@ where
     }
 }
 
+impl<IO> Connect<IO, Server>
+where
+    IO: Pipe,
+{
+    /// Announce the host keys of the `server` to the _client_ with the `hostkeys-00@openssh.com`
+    /// _global request_, to be sent once the user is authenticated, for the _client_ to learn
+    /// the keys that are rotated in, and forget the ones that are rotated out.
+    ///
+    /// The host keys are already announced when the [`Service`] starts,
+    /// unless disabled with [`Server::announce_host_keys`].
+    ///
+    /// The _client_ may then verify them with `hostkeys-prove-00@openssh.com` requests,
+    /// to be answered with [`global_request::GlobalRequest::prove`].
+    pub async fn announce_host_keys(&self, server: &Server) -> Result<()> {
+        let keys = server
+            .public_keys()
+            .map(|key| key.to_bytes().map(Into::into))
+            .collect::<Result<_, _>>()
+            .map_err(assh::Error::from)?;
+
+        self.global_request(global_request::GlobalRequestContext::HostKeys { keys })
+            .await
+    }
+}
+
 impl<IO, S> Drop for Connect<IO, S>
 where
     IO: Pipe,
@@ -2,6 +2,7 @@ use assh::{Pipe, Session, service, side::Side};
 use ssh_packet::arch::{Ascii, ascii};
 
 use super::Connect;
+use crate::global_request;
 
 const SERVICE_NAME: Ascii<'static> = ascii!("ssh-connection");
 
@@ -22,7 +23,24 @@ impl service::Handler for Service {
         IO: Pipe,
         S: Side,
     {
-        Ok(Connect::new(session))
+        let keys = session
+            .announced_keys()
+            .map(|key| key.to_bytes().map(Into::into))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let connect = Connect::new(session);
+
+        if !keys.is_empty() {
+            connect
+                .mux
+                .send(&global_request::Message {
+                    want_reply: false.into(),
+                    context: global_request::GlobalRequestContext::HostKeys { keys },
+                })
+                .await?;
+        }
+
+        Ok(connect)
     }
 }
 
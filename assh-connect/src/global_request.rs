@@ -1,12 +1,111 @@
 //! The _global requests_ and responses.
 
-use assh::{Pipe, side::Side};
-use ssh_packet::connect;
+use assh::{
+    Pipe,
+    extensions::{HOSTKEYS, HOSTKEYS_PROVE},
+    side::{Side, server::Server},
+};
+use ssh_packet::{
+    arch,
+    binrw::{self, binrw},
+    connect,
+};
 
 use crate::{Result, mux::Mux};
 
-#[doc(no_inline)]
-pub use ssh_packet::connect::GlobalRequestContext;
+/// The `SSH_MSG_GLOBAL_REQUEST` message, with the contexts of the OpenSSH extensions.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4254#section-4>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 80_u8)]
+pub(crate) struct Message<'b> {
+    #[bw(calc = context.as_ascii())]
+    kind: arch::Ascii<'b>,
+
+    /// Whether the sender wants a reply.
+    pub want_reply: arch::Bool,
+
+    /// The context of the global request.
+    #[br(args(kind))]
+    pub context: GlobalRequestContext<'b>,
+}
+
+/// The `SSH_MSG_REQUEST_SUCCESS` message in response to a `hostkeys-prove-00@openssh.com` request.
+///
+/// see <https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 81_u8)]
+pub(crate) struct HostKeysProof {
+    /// Signatures of the requested host keys, in the order of the request.
+    #[br(parse_with = binrw::helpers::until_eof)]
+    pub signatures: Vec<arch::Bytes<'static>>,
+}
+
+/// The `context` in the `SSH_MSG_GLOBAL_REQUEST` message.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big)]
+#[br(import(kind: arch::Ascii<'_>))]
+pub enum GlobalRequestContext<'b> {
+    /// A request of type `tcpip-forward`,
+    /// as defined in [RFC4254 section 7.1](https://datatracker.ietf.org/doc/html/rfc4254#section-7.1).
+    #[br(pre_assert(kind == GlobalRequestContext::TCPIP_FORWARD))]
+    TcpipForward {
+        /// Address to bind on the remote.
+        bind_address: arch::Bytes<'b>,
+
+        /// Port to bind on the remote, randomly choosen if 0.
+        bind_port: u32,
+    },
+
+    /// A request of type `cancel-tcpip-forward`,
+    /// as defined in [RFC4254 section 7.1](https://datatracker.ietf.org/doc/html/rfc4254#section-7.1).
+    #[br(pre_assert(kind == GlobalRequestContext::CANCEL_TCPIP_FORWARD))]
+    CancelTcpipForward {
+        /// Address that was bound on the remote.
+        bind_address: arch::Bytes<'b>,
+
+        /// Port that was bound on the remote.
+        bind_port: u32,
+    },
+
+    /// A request of type `hostkeys-00@openssh.com`, announcing the _server host keys_,
+    /// as defined in [OpenSSH's PROTOCOL](https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD).
+    #[br(pre_assert(&*kind == HOSTKEYS))]
+    HostKeys {
+        /// Public key blobs of the host keys.
+        #[br(parse_with = binrw::helpers::until_eof)]
+        keys: Vec<arch::Bytes<'static>>,
+    },
+
+    /// A request of type `hostkeys-prove-00@openssh.com`, requesting proofs of possession of _server host keys_,
+    /// as defined in [OpenSSH's PROTOCOL](https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD).
+    #[br(pre_assert(&*kind == HOSTKEYS_PROVE))]
+    HostKeysProve {
+        /// Public key blobs of the host keys to prove.
+        #[br(parse_with = binrw::helpers::until_eof)]
+        keys: Vec<arch::Bytes<'static>>,
+    },
+}
+
+impl GlobalRequestContext<'_> {
+    const TCPIP_FORWARD: arch::Ascii<'static> = arch::ascii!("tcpip-forward");
+    const CANCEL_TCPIP_FORWARD: arch::Ascii<'static> = arch::ascii!("cancel-tcpip-forward");
+
+    /// Get the [`GlobalRequestContext`]'s SSH identifier.
+    pub fn as_ascii(&self) -> arch::Ascii<'static> {
+        match self {
+            Self::TcpipForward { .. } => Self::TCPIP_FORWARD,
+            Self::CancelTcpipForward { .. } => Self::CANCEL_TCPIP_FORWARD,
+            Self::HostKeys { .. } => arch::Ascii::borrowed(HOSTKEYS).expect("the name is ASCII"),
+            Self::HostKeysProve { .. } => {
+                arch::Ascii::borrowed(HOSTKEYS_PROVE).expect("the name is ASCII")
+            }
+        }
+    }
+}
 
 /// A response to a _global request_.
 #[derive(Debug)]
@@ -14,6 +113,10 @@ pub enum Response {
     /// The request succeeded, with optionally a bound port.
     Success(Option<u32>),
 
+    /// The `hostkeys-prove-00@openssh.com` request succeeded,
+    /// with the signatures of the requested host keys.
+    Proof(Vec<Vec<u8>>),
+
     /// The request failed.
     Failure,
 }
@@ -21,11 +124,11 @@ pub enum Response {
 /// A received _global request_.
 pub struct GlobalRequest<'s, IO: Pipe, S: Side> {
     mux: &'s Mux<IO, S>,
-    inner: Option<connect::GlobalRequest<'static>>,
+    inner: Option<Message<'static>>,
 }
 
 impl<'s, IO: Pipe, S: Side> GlobalRequest<'s, IO, S> {
-    pub(super) fn new(mux: &'s Mux<IO, S>, inner: connect::GlobalRequest<'static>) -> Self {
+    pub(super) fn new(mux: &'s Mux<IO, S>, inner: Message<'static>) -> Self {
         Self {
             mux,
             inner: Some(inner),
@@ -41,7 +144,7 @@ impl<'s, IO: Pipe, S: Side> GlobalRequest<'s, IO, S> {
 
         if *inner.want_reply {
             match inner.context {
-                connect::GlobalRequestContext::TcpipForward { bind_port: 0, .. } => {
+                GlobalRequestContext::TcpipForward { bind_port: 0, .. } => {
                     self.mux
                         .send(&connect::ForwardingSuccess { bound_port })
                         .await?
@@ -73,7 +176,7 @@ impl<'s, IO: Pipe, S: Side> GlobalRequest<'s, IO, S> {
     }
 
     /// Access the _context_ of the global request.
-    pub fn cx(&self) -> &connect::GlobalRequestContext<'_> {
+    pub fn cx(&self) -> &GlobalRequestContext<'_> {
         &self
             .inner
             .as_ref()
@@ -82,6 +185,49 @@ impl<'s, IO: Pipe, S: Side> GlobalRequest<'s, IO, S> {
     }
 }
 
+impl<'s, IO: Pipe> GlobalRequest<'s, IO, Server> {
+    /// Answer a `hostkeys-prove-00@openssh.com` request with the proofs of possession of the
+    /// requested host keys of the `server`, rejecting it if one of them isn't a host key,
+    /// or if the global request is of another type.
+    pub async fn prove(mut self, server: &Server) -> Result<()> {
+        let inner = self
+            .inner
+            .take()
+            .expect("Inner value has been dropped before the outer structure");
+
+        let signatures = match (
+            &inner.context,
+            &self.mux.session_id,
+            &self.mux.host_key().await,
+        ) {
+            (GlobalRequestContext::HostKeysProve { keys }, Some(session_id), Some(algorithm)) => {
+                server.prove(session_id, algorithm, keys).await?
+            }
+            _ => None,
+        };
+
+        if *inner.want_reply {
+            match signatures {
+                Some(signatures) => {
+                    let signatures = signatures
+                        .into_iter()
+                        .map(|signature| Vec::try_from(signature).map(Into::into))
+                        .collect::<Result<_, _>>()
+                        .map_err(assh::Error::from)?;
+
+                    self.mux.send(&HostKeysProof { signatures }).await?
+                }
+                None => {
+                    Self::rejected(self.mux);
+                    self.mux.flush().await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<'s, IO: Pipe, S: Side> Drop for GlobalRequest<'s, IO, S> {
     fn drop(&mut self) {
         if matches!(&self.inner, Some(inner) if *inner.want_reply) {
@@ -1,8 +1,12 @@
-use assh::{Pipe, Session, side::Side};
+use std::sync::Arc;
+
+use assh::{Pipe, Session, algorithm::Key, side::Side};
 use dashmap::DashMap;
 use futures::{FutureExt, lock::Mutex, task};
 use ssh_packet::{IntoPacket, Packet, binrw, connect};
 
+use crate::channel;
+
 mod interest;
 pub use interest::Interest;
 
@@ -19,6 +23,10 @@ pub struct Mux<IO: Pipe, S: Side> {
     poller: Mutex<Poller<IO, S>>,
     interests: DashMap<Interest, task::AtomicWaker>,
     pub(crate) channels: Slots<u32, CHANNEL_MAX_COUNT>,
+    pub(crate) opened: DashMap<u32, Arc<channel::State>>,
+
+    /// The session identifier, binding the proofs of `hostkeys-prove-00@openssh.com`.
+    pub(crate) session_id: Option<Vec<u8>>,
 }
 
 impl<IO, S> From<Session<IO, S>> for Mux<IO, S>
@@ -27,6 +35,8 @@ where
     S: Side,
 {
     fn from(session: Session<IO, S>) -> Self {
+        let session_id = session.session_id().map(ToOwned::to_owned);
+
         let (poller, queue) = Poller::new(session);
 
         Self {
@@ -34,6 +44,8 @@ where
             poller: poller.into(),
             interests: Default::default(),
             channels: Default::default(),
+            opened: Default::default(),
+            session_id,
         }
     }
 }
@@ -147,7 +159,7 @@ where
                             task::Poll::Pending
                         }
                         None => {
-                            if let Ok(message) = packet.to::<connect::GlobalRequest>() {
+                            if let Ok(message) = packet.to::<crate::global_request::Message>() {
                                 tracing::debug!(
                                     "{packet_interest:?}: Rejectected an unhandled `GlobalRequest`"
                                 );
@@ -214,4 +226,9 @@ where
         self.feed(item);
         self.flush().await
     }
+
+    /// The negociated _server host key_ algorithm, as of the latest key-exchange.
+    pub async fn host_key(&self) -> Option<Key> {
+        self.poller.lock().await.host_key().cloned()
+    }
 }
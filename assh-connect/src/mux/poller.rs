@@ -1,4 +1,4 @@
-use assh::{Pipe, Session, side::Side};
+use assh::{Pipe, Session, algorithm::Key, side::Side};
 use futures::{FutureExt, future::BoxFuture, task};
 use ssh_packet::Packet;
 
@@ -24,6 +24,9 @@ pub struct Poller<IO: Pipe, S: Side> {
 
     /// Message awaiting to be popped by the local asynchronous tasks.
     buffer: Option<Packet>,
+
+    /// The negociated _server host key_ algorithm, as of the latest key-exchange.
+    host_key: Option<Key>,
 }
 
 impl<IO, S> Poller<IO, S>
@@ -36,6 +39,9 @@ where
 
         (
             Self {
+                host_key: session
+                    .negociated()
+                    .map(|negociated| negociated.key.clone()),
                 state: State::Idle(Some(session.into())),
 
                 queue: rx,
@@ -44,6 +50,20 @@ where
             tx,
         )
     }
+
+    /// Access the negociated _server host key_ algorithm, as of the latest key-exchange.
+    pub fn host_key(&self) -> Option<&Key> {
+        self.host_key.as_ref()
+    }
+
+    /// Put back the [`Session`] to idle after an operation,
+    /// which may have gone through a key re-exchange.
+    fn idle(&mut self, session: Box<Session<IO, S>>) {
+        self.host_key = session
+            .negociated()
+            .map(|negociated| negociated.key.clone());
+        self.state = State::Idle(Some(session));
+    }
 }
 
 /// Methods used to _receive_ messages from the [`Session`].
@@ -83,7 +103,7 @@ where
                     result.as_ref().map(|packet| packet[0])
                 );
 
-                self.state = State::Idle(Some(session));
+                self.idle(session);
 
                 task::Poll::Ready(Some(result))
             }
@@ -122,7 +142,7 @@ where
             State::Sending(fut) => {
                 let (result, session) = futures::ready!(fut.poll_unpin(cx));
 
-                self.state = State::Idle(Some(session));
+                self.idle(session);
                 result?;
 
                 cx.waker().wake_by_ref();
@@ -0,0 +1,127 @@
+use assh::{
+    algorithm::Key,
+    side::{
+        client::{Client, PublicKey},
+        server::{PrivateKey, Server},
+    },
+};
+use assh_connect::global_request::{GlobalRequestContext, Response};
+
+use async_compat::CompatExt;
+use futures::TryStreamExt;
+use signature::Verifier;
+use tokio::io::BufStream;
+
+/// The data signed by the _server_ to prove the possession of the host key `blob`.
+fn proof(session_id: &[u8], blob: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+
+    for field in [
+        b"hostkeys-prove-00@openssh.com".as_slice(),
+        session_id,
+        blob,
+    ] {
+        data.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        data.extend_from_slice(field);
+    }
+
+    data
+}
+
+#[tokio::test]
+async fn announce_and_prove() -> Result<(), eyre::Error> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let duplex = tokio::io::duplex(ssh_packet::Packet::MAX_SIZE * 16);
+    let server = Server {
+        keys: vec![
+            PrivateKey::random(&mut rand::rng(), Key::Ed25519)?,
+            PrivateKey::from(ssh_key::private::RsaKeypair::random(
+                &mut rand::rng(),
+                2048,
+            )?),
+        ],
+        ..Default::default()
+    };
+    let unknown = PrivateKey::random(&mut rand::rng(), Key::Ed25519)?;
+
+    tokio::try_join!(
+        async {
+            let session =
+                assh::Session::new(BufStream::new(duplex.0).compat(), server.clone()).await?;
+
+            // The host keys are announced as the service starts.
+            let connect = session.handle(assh_connect::Service).await?;
+
+            let mut requests = connect.global_requests();
+            for _ in 0..2 {
+                let request = requests
+                    .try_next()
+                    .await?
+                    .expect("Disconnected before receiving the proof requests");
+                assert!(matches!(
+                    request.cx(),
+                    GlobalRequestContext::HostKeysProve { .. }
+                ));
+
+                request.prove(&server).await?;
+            }
+
+            Ok::<_, eyre::Error>(())
+        },
+        async {
+            let session =
+                assh::Session::new(BufStream::new(duplex.1).compat(), Client::default()).await?;
+
+            let connect = session.request(assh_connect::Service).await?;
+            let session_id = connect
+                .session_id()
+                .expect("The key-exchange has not been completed")
+                .to_vec();
+
+            let request = connect
+                .global_requests()
+                .try_next()
+                .await?
+                .expect("Disconnected before receiving the host keys");
+            let GlobalRequestContext::HostKeys { keys } = request.cx().clone() else {
+                panic!("Unexpected global request `{:?}`", request.cx())
+            };
+            drop(request);
+
+            let announced = keys
+                .iter()
+                .map(|blob| PublicKey::from_bytes(blob))
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(announced, server.public_keys().cloned().collect::<Vec<_>>());
+
+            let Response::Proof(signatures) = connect
+                .global_request_wait(GlobalRequestContext::HostKeysProve { keys: keys.clone() })
+                .await?
+            else {
+                panic!("The host keys were not proven by the server")
+            };
+            assert_eq!(signatures.len(), announced.len());
+
+            for ((key, blob), signature) in announced.iter().zip(&keys).zip(&signatures) {
+                let signature = ssh_key::Signature::try_from(signature.as_slice())?;
+
+                Verifier::verify(key, &proof(&session_id, blob), &signature)?;
+            }
+
+            let response = connect
+                .global_request_wait(GlobalRequestContext::HostKeysProve {
+                    keys: vec![unknown.public_key().to_bytes()?.into()],
+                })
+                .await?;
+            assert!(matches!(response, Response::Failure));
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
@@ -81,10 +81,17 @@ where
                 panic!("Channel opening rejected server-side")
             };
 
+            let channels = connect.channels();
+            assert_eq!(channels.len(), 1);
+            assert_eq!(channels[0].kind, "session");
+            assert_eq!(channels[0].local_id, channel.info().local_id);
+
             clientside(channel)
                 .instrument(tracing::span!(tracing::Level::INFO, "client"))
                 .await;
 
+            assert!(connect.channels().is_empty());
+
             Ok(())
         }
         .inspect_err(|err: &eyre::Error| tracing::error!("An error occured client-side: {err}")),
@@ -133,7 +140,12 @@ async fn small() -> Result<(), eyre::Error> {
                     async { sha1(&mut channel.as_reader()).await.unwrap() }
                 );
 
-                assert_eq!(sent.finalize(), recvd.finalize())
+                assert_eq!(sent.finalize(), recvd.finalize());
+
+                let info = channel.info();
+                assert_eq!(info.kind, "session");
+                assert_eq!(info.tx_bytes, 8192);
+                assert_eq!(info.rx_bytes, 8192);
             }
             .boxed()
         },
@@ -13,6 +13,42 @@ pub enum DisconnectedBy {
     Them,
 }
 
+/// The deadline of the session that expired for [`Error::Timeout`].
+#[derive(Debug, Clone, Copy)]
+pub enum Expiry {
+    /// The identification strings were not exchanged in time.
+    Id,
+
+    /// The key-exchange was not completed in time.
+    Kex,
+
+    /// The user was not authenticated in time.
+    Auth,
+
+    /// The peer sent nothing for too long.
+    Idle,
+}
+
+impl Expiry {
+    /// The disconnect reason to report to the peer.
+    pub(crate) fn reason(self) -> trans::DisconnectReason {
+        match self {
+            Self::Auth => trans::DisconnectReason::ByApplication,
+            Self::Id | Self::Kex | Self::Idle => trans::DisconnectReason::ConnectionLost,
+        }
+    }
+
+    /// The disconnect description to report to the peer.
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Self::Id => "Timed out during the identification exchange",
+            Self::Kex => "Timed out during the key-exchange",
+            Self::Auth => "Timed out before authentication",
+            Self::Idle => "Timed out due to inactivity",
+        }
+    }
+}
+
 /// The error type describing disconnect.
 #[must_use]
 #[derive(Debug, Error, Clone)]
@@ -28,6 +64,29 @@ pub struct DisconnectedError {
     pub description: Utf8<'static>,
 }
 
+/// The error type of algorithm lists in the OpenSSH syntax,
+/// see [`algorithm::alter`](crate::algorithm::alter).
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ListError {
+    /// The name or pattern matches none of the supported algorithms.
+    #[error("Unknown algorithm `{name}`, supported algorithms are `{}`", supported.join(","))]
+    Unknown {
+        /// The name or pattern as written in the list.
+        name: String,
+
+        /// The names of the supported algorithms of this kind.
+        supported: Vec<String>,
+    },
+
+    /// The list resolves to no algorithm at all.
+    #[error("The algorithm list `{0}` leaves no algorithm enabled")]
+    Empty(String),
+
+    /// The option is not an algorithm list, in [`Algorithms::apply`](crate::side::server::Algorithms::apply).
+    #[error("Unknown algorithm option `{0}`")]
+    Option(String),
+}
+
 /// The error types that can occur when manipulating this crate.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -76,6 +135,10 @@ pub enum Error {
     #[error("Unable to negociate a common compression algorithm")]
     NoCommonCompression,
 
+    /// The host key presented by the server was rejected by the verifier.
+    #[error("The server host key could not be verified")]
+    HostKeyNotVerifiable,
+
     /// Protocol error in the key-exchange.
     #[error("Error in the kex-exchange algorithm")]
     KexError,
@@ -88,6 +151,10 @@ pub enum Error {
     #[error("Peer sent a message that made no sense in the current context")]
     UnexpectedMessage,
 
+    /// A deadline of the session expired while awaiting data from the peer.
+    #[error("Timed out awaiting data from the peer: {0:?}")]
+    Timeout(Expiry),
+
     /// The session has been disconnected.
     #[error(transparent)]
     Disconnected(#[from] DisconnectedError),
@@ -27,7 +27,7 @@ pub mod error;
 pub use error::{Error, Result};
 
 mod session;
-pub use session::{Pipe, Session, service, side};
+pub use session::{Directional, Negociated, Pipe, Session, Stats, extensions, service, side};
 
 mod stream;
 
@@ -35,6 +35,11 @@ pub mod algorithm {
     //! Supported algorithms for **compression**, **encryption**, **integrity**, **key-exchange** & **server key**.
 
     pub use crate::stream::algorithm::{
-        cipher::Cipher, compress::Compress, hmac::Hmac, kex::Kex, key::Key,
+        cipher::Cipher,
+        compress::Compress,
+        hmac::Hmac,
+        kex::Kex,
+        key::Key,
+        list::{Named, alter},
     };
 }
@@ -0,0 +1,177 @@
+//! Extension negotiation in the _SSH_ protocol, as per RFC 8308,
+//! and the messages of the negotiated extensions.
+
+use ssh_packet::{
+    arch,
+    binrw::{self, binrw},
+};
+
+use crate::stream::algorithm::key::Key;
+
+/// The `server-sig-algs` extension name.
+pub const SERVER_SIG_ALGS: &str = "server-sig-algs";
+
+/// The `publickey-hostbound@openssh.com` extension name.
+pub const PUBLICKEY_HOSTBOUND: &str = "publickey-hostbound@openssh.com";
+
+/// The `ping@openssh.com` extension name.
+pub const PING: &str = "ping@openssh.com";
+
+/// The `hostkeys-00@openssh.com` global request name, announcing the _server host keys_.
+pub const HOSTKEYS: &str = "hostkeys-00@openssh.com";
+
+/// The `hostkeys-prove-00@openssh.com` global request name, proving the possession of _server host keys_.
+pub const HOSTKEYS_PROVE: &str = "hostkeys-prove-00@openssh.com";
+
+/// The `SSH_MSG_EXT_INFO` message.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc8308#section-2.3>.
+#[binrw]
+#[derive(Debug, Default, Clone)]
+#[brw(big, magic = 7_u8)]
+pub struct ExtInfo {
+    #[bw(calc = extensions.len() as u32)]
+    count: u32,
+
+    /// The extensions advertised by the sender.
+    #[br(count = count)]
+    pub extensions: Vec<Extension>,
+}
+
+/// An extension in the `SSH_MSG_EXT_INFO` message.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big)]
+pub struct Extension {
+    /// Name of the extension.
+    pub name: arch::Ascii<'static>,
+
+    /// Value of the extension, whose format is specific to the extension.
+    pub value: arch::Bytes<'static>,
+}
+
+/// The `SSH2_MSG_PING` message, from the `ping@openssh.com` extension.
+///
+/// see <https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD>.
+#[binrw]
+#[derive(Debug, Default, Clone)]
+#[brw(big, magic = 192_u8)]
+pub struct Ping<'b> {
+    /// Data to be echoed back by the peer.
+    pub data: arch::Bytes<'b>,
+}
+
+/// The `SSH2_MSG_PONG` message, from the `ping@openssh.com` extension.
+///
+/// see <https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD>.
+#[binrw]
+#[derive(Debug, Default, Clone)]
+#[brw(big, magic = 193_u8)]
+pub struct Pong<'b> {
+    /// Data echoed back from the [`Ping`] message.
+    pub data: arch::Bytes<'b>,
+}
+
+/// The extensions received from the peer in `SSH_MSG_EXT_INFO` messages.
+#[derive(Debug, Default, Clone)]
+pub struct Extensions {
+    received: Vec<(String, Vec<u8>)>,
+}
+
+impl Extensions {
+    /// Record the extensions of a received [`ExtInfo`] message,
+    /// superseding the values of previously received ones.
+    pub(crate) fn extend(&mut self, info: ExtInfo) {
+        for Extension { name, value } in info.extensions {
+            let value = value.into_vec();
+
+            match self.received.iter_mut().find(|(known, _)| *known == *name) {
+                Some((_, known)) => *known = value,
+                None => self.received.push((name.into_string(), value)),
+            }
+        }
+    }
+
+    /// Iterate over the received extensions, as pairs of name and raw value.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.received
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_slice()))
+    }
+
+    /// Get the raw value of the extension `name`, if received.
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.iter()
+            .find_map(|(known, value)| (known == name).then_some(value))
+    }
+
+    /// The signature algorithms accepted by the _server_ for _public key_ authentication,
+    /// from the `server-sig-algs` extension.
+    pub fn server_sig_algs(&self) -> Option<Vec<Key>> {
+        let value = std::str::from_utf8(self.get(SERVER_SIG_ALGS)?).ok()?;
+
+        Some(
+            value
+                .split(',')
+                .filter_map(|name| Key::new(name).ok())
+                .collect(),
+        )
+    }
+
+    /// Whether the _server_ supports the host-bound _public key_ authentication,
+    /// from the `publickey-hostbound@openssh.com` extension.
+    pub fn publickey_hostbound(&self) -> bool {
+        self.get(PUBLICKEY_HOSTBOUND) == Some(b"0")
+    }
+
+    /// Whether the peer answers to [`Ping`] messages,
+    /// from the `ping@openssh.com` extension.
+    pub fn ping(&self) -> bool {
+        self.get(PING) == Some(b"0")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssh_packet::{IntoPacket, arch::Ascii};
+
+    use super::*;
+
+    #[test]
+    fn it_supersedes_previously_received_extensions() {
+        let info = |value: &'static str| ExtInfo {
+            extensions: vec![
+                Extension {
+                    name: Ascii::borrowed(SERVER_SIG_ALGS).expect("the name is ASCII"),
+                    value: value.as_bytes().into(),
+                },
+                Extension {
+                    name: Ascii::borrowed(PING).expect("the name is ASCII"),
+                    value: b"0".as_slice().into(),
+                },
+            ],
+        };
+
+        let mut extensions = Extensions::default();
+        extensions.extend(
+            (&info("ssh-ed25519,unknown"))
+                .into_packet()
+                .to()
+                .expect("Unable to parse back the `SSH_MSG_EXT_INFO` message"),
+        );
+        extensions.extend(info("rsa-sha2-512,ssh-ed25519"));
+
+        assert_eq!(extensions.iter().count(), 2);
+        assert_eq!(
+            extensions.server_sig_algs(),
+            Some(vec![
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha512)
+                },
+                Key::Ed25519
+            ])
+        );
+        assert!(extensions.ping());
+        assert!(!extensions.publickey_hostbound());
+    }
+}
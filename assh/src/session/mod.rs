@@ -1,3 +1,5 @@
+use std::{collections::VecDeque, time::Instant};
+
 use either::Either;
 use futures::{AsyncBufRead, AsyncWrite, AsyncWriteExt};
 use ssh_packet::{
@@ -7,29 +9,71 @@ use ssh_packet::{
         Debug, Disconnect, DisconnectReason, Ignore, KexInit, ServiceAccept, ServiceRequest,
         Unimplemented,
     },
+    userauth,
 };
 
 use crate::{
-    error::{DisconnectedBy, DisconnectedError, Error, Result},
-    stream::Stream,
+    error::{DisconnectedBy, DisconnectedError, Error, Expiry, Result},
+    stream::{self, Stream},
 };
 
+pub mod extensions;
+use extensions::{ExtInfo, Extensions, Ping, Pong};
+
+mod negociated;
+pub use negociated::{Directional, Negociated};
+
+pub use crate::stream::Stats;
+
 pub mod service;
 pub mod side;
 
-// TODO: (feature) Handle extension negotiation described in RFC8308.
-// TODO: (reliability) Fix out-of-band rekeying, it expects the packet right away while we are not sure the peer is that fast.
-
 /// A trait alias for something _pipe-alike_, implementing [`AsyncBufRead`] and [`AsyncWrite`].
 pub trait Pipe: AsyncBufRead + AsyncWrite + Unpin + Send + Sync + 'static {}
 impl<T: AsyncBufRead + AsyncWrite + Unpin + Send + Sync + 'static> Pipe for T {}
 
+/// The number of packets queued during a key re-exchange,
+/// after which [`Session::send`] drives the exchange to completion.
+const QUEUE_LIMIT: usize = 64;
+
+/// The state of the key-exchange in the [`Session`].
+enum Kex {
+    /// No key-exchange is in progress.
+    Idle,
+
+    /// Our [`KexInit`] has been sent at the instant, awaiting the peer's one.
+    Init(Box<KexInit<'static>>, Instant),
+}
+
 /// A session wrapping a `stream` to handle **key-exchange** and **`SSH-TRANS`** layer messages.
 pub struct Session<IO: Pipe, S: side::Side> {
     stream: Either<Stream<IO>, DisconnectedError>,
     config: S,
 
     peer_id: Id,
+    extensions: Extensions,
+    negociated: Option<Negociated>,
+
+    kex: Kex,
+
+    /// Start of the session, for the authentication deadline.
+    started: Instant,
+
+    /// Last reception of a packet, for the inactivity deadline.
+    active: Instant,
+
+    /// Final statistics of the stream, once disconnected.
+    stats: Stats,
+
+    /// Packets sent during the key-exchange, to be flushed once it completes.
+    queue: VecDeque<Packet>,
+
+    /// Whether the delayed compression is to be activated once the queued
+    /// `SSH_MSG_USERAUTH_SUCCESS` message is flushed.
+    authenticating: bool,
+
+    /// Packets received while [`Session::send`] drove the key-exchange, to be returned by [`Session::recv`].
+    inbound: VecDeque<Packet>,
 }
 
 impl<IO, S> Session<IO, S>
@@ -40,11 +84,41 @@ where
     /// Create a new [`Session`] from a [`Pipe`] stream,
     /// and some configuration.
     pub async fn new(mut stream: IO, config: S) -> Result<Self> {
-        config.id().to_writer(&mut stream).await?;
-        stream.flush().await?;
+        let started = Instant::now();
 
-        let peer_id = Id::from_reader(&mut stream).await?;
-        let stream = Stream::new(stream);
+        let exchange = async {
+            config.id().to_writer(&mut stream).await?;
+            stream.flush().await?;
+
+            Ok::<_, Error>(Id::from_reader(&mut stream).await?)
+        };
+        let deadline = config
+            .timeouts()
+            .id
+            .map(|timeout| (started + timeout, Expiry::Id));
+        let peer_id = stream::timeout(exchange, deadline).await;
+
+        let mut stream = Stream::new(stream, config.rekey_limit().clone());
+        let peer_id = match peer_id {
+            Ok(peer_id) => peer_id?,
+            Err(expiry) => {
+                tracing::info!("Disconnecting peer: {}", expiry.description());
+
+                let message = Disconnect {
+                    reason: expiry.reason(),
+                    description: expiry.description().into(),
+                    language: Default::default(),
+                };
+                stream.send(&message).await.ok();
+
+                return Err(DisconnectedError {
+                    by: DisconnectedBy::Us,
+                    reason: message.reason,
+                    description: message.description,
+                }
+                .into());
+            }
+        };
 
         tracing::debug!("Session started with peer `{peer_id}`");
 
@@ -52,6 +126,15 @@ where
             stream: Either::Left(stream),
             config,
             peer_id,
+            extensions: Default::default(),
+            negociated: None,
+            kex: Kex::Idle,
+            started,
+            active: started,
+            stats: Default::default(),
+            queue: Default::default(),
+            authenticating: false,
+            inbound: Default::default(),
         })
     }
 
@@ -60,90 +143,333 @@ where
         &self.peer_id
     }
 
+    /// Access the [`Extensions`] received from the connected peer.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Access the algorithms [`Negociated`] with the connected peer in the last key-exchange,
+    /// or `None` until the initial one completes.
+    pub fn negociated(&self) -> Option<&Negociated> {
+        self.negociated.as_ref()
+    }
+
+    /// A snapshot of the cumulative [`Stats`] of the session,
+    /// which are retained after the disconnection.
+    pub fn stats(&self) -> Stats {
+        match &self.stream {
+            Either::Left(stream) => stream.stats(),
+            Either::Right(_) => self.stats.clone(),
+        }
+    }
+
+    /// Access the public host keys to announce to the peer once the connection service starts,
+    /// as configured with [`Server::announce_host_keys`](side::server::Server::announce_host_keys).
+    pub fn announced_keys(&self) -> impl Iterator<Item = &ssh_key::PublicKey> {
+        self.config.announced_keys()
+    }
+
     /// Access initial exchange hash.
     pub fn session_id(&self) -> Option<&[u8]> {
         self.stream.as_ref().left().and_then(Stream::session_id)
     }
 
+    /// Mark the user as authenticated, which activates the delayed compression algorithms
+    /// (`zlib@openssh.com`) for the packets following the `SSH_MSG_USERAUTH_SUCCESS` message.
+    ///
+    /// This is to be called by the _authentication_ service,
+    /// right after sending or receiving the success message.
+    pub fn set_authenticated(&mut self) {
+        // The success message may still be queued behind a key re-exchange,
+        // in which case the activation is deferred until it is actually written.
+        if self
+            .queue
+            .iter()
+            .any(|packet| packet.to::<userauth::Success>().is_ok())
+        {
+            self.authenticating = true;
+        } else if let Either::Left(stream) = &mut self.stream {
+            stream.set_authenticated();
+        }
+    }
+
     /// Waits until the [`Session`] becomes readable,
     /// mainly to be used with [`Session::recv`] in [`futures::select`],
     /// since the `recv` method is **not cancel-safe**.
     pub async fn readable(&mut self) -> Result<()> {
-        let stream = match &mut self.stream {
-            Either::Left(stream) => stream,
-            Either::Right(err) => return Err(err.clone().into()),
-        };
+        if !self.inbound.is_empty() {
+            return Ok(());
+        }
 
-        stream.fill_buf().await
+        let deadline = self.deadline();
+        let stream = self.stream()?;
+        stream.set_deadline(deadline);
+
+        match stream.fill_buf().await {
+            Err(Error::Timeout(expiry)) => Err(self.expire(expiry).await.into()),
+            result => result,
+        }
     }
 
     /// Receive a _packet_ from the connected peer.
     ///
+    /// The peer is disconnected if any of the [`side::Side::timeouts`] expires meanwhile.
+    ///
     /// # Cancel safety
     /// This method is **not cancel-safe**, if used within a [`futures::select`] call,
     /// some data may be partially received.
     pub async fn recv(&mut self) -> Result<Packet> {
-        loop {
-            let stream = match &mut self.stream {
-                Either::Left(stream) => stream,
-                Either::Right(err) => return Err(err.clone().into()),
-            };
-
-            if stream.should_rekey() || stream.peek().await?.to::<KexInit>().is_ok() {
-                if let Err(err) = self.config.kex(stream, &self.peer_id).await {
-                    return Err(self
-                        .disconnect(DisconnectReason::KeyExchangeFailed, err.to_string())
-                        .await
-                        .into());
-                }
-
-                continue;
-            }
-
-            let packet = stream.recv().await?;
+        if let Some(packet) = self.inbound.pop_front() {
+            return Ok(packet);
+        }
 
-            if let Ok(Disconnect {
-                reason,
-                description,
-                ..
-            }) = packet.to()
-            {
-                tracing::info!("Peer disconnected with `{reason:?}`: {description}");
-
-                self.stream = Either::Right(DisconnectedError {
-                    by: DisconnectedBy::Them,
-                    reason,
-                    description,
-                });
-            } else if let Ok(Ignore { data }) = packet.to() {
-                tracing::debug!("Received an 'ignore' message with length {}", data.len());
-            } else if let Ok(Unimplemented { seq }) = packet.to() {
-                tracing::debug!("Received an 'unimplemented' message about packet #{seq}",);
-            } else if let Ok(Debug { message, .. }) = packet.to() {
-                tracing::debug!("Received a 'debug' message: {message}");
-            } else {
+        loop {
+            if let Some(packet) = self.process().await? {
                 break Ok(packet);
             }
         }
     }
 
     /// Send a _packet_ to the connected peer.
+    ///
+    /// While a key re-exchange is in progress, the packet is queued
+    /// to be sent once the exchange completes in a subsequent [`Session::recv`],
+    /// or in this call if too many packets are queued, while the packets received
+    /// meanwhile are kept for the subsequent calls to [`Session::recv`].
     pub async fn send(&mut self, message: impl IntoPacket) -> Result<()> {
-        let stream = match &mut self.stream {
-            Either::Left(stream) => stream,
-            Either::Right(err) => return Err(err.clone().into()),
+        // The initial key-exchange is to be completed before any packet is sent.
+        while self.stream()?.session_id().is_none() {
+            self.process().await?;
+        }
+
+        self.transmit(message.into_packet()).await?;
+
+        while self.queue.len() >= QUEUE_LIMIT {
+            if let Some(packet) = self.process().await? {
+                self.inbound.push_back(packet);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Initiate a key re-exchange with the peer, if none is already in progress.
+    ///
+    /// The exchange is completed in the subsequent calls to [`Session::recv`],
+    /// and the packets sent meanwhile are queued until it completes, see [`Session::send`].
+    pub async fn rekey(&mut self) -> Result<()> {
+        if let Kex::Idle = self.kex {
+            let kexinit = self.config.kexinit();
+            self.stream()?.send(&kexinit).await?;
+
+            tracing::debug!("Initiated the key-exchange procedure");
+
+            self.kex = Kex::Init(kexinit.into(), Instant::now());
+        }
+
+        Ok(())
+    }
+
+    fn stream(&mut self) -> Result<&mut Stream<IO>> {
+        match &mut self.stream {
+            Either::Left(stream) => Ok(stream),
+            Either::Right(err) => Err(err.clone().into()),
+        }
+    }
+
+    /// The earliest deadline of the session according to the [`side::Side::timeouts`],
+    /// along with the kind of deadline.
+    fn deadline(&self) -> Option<(Instant, Expiry)> {
+        let timeouts = self.config.timeouts();
+        let authenticated = self
+            .stream
+            .as_ref()
+            .left()
+            .is_some_and(Stream::is_authenticated);
+
+        let kex = match self.kex {
+            Kex::Init(_, since) => timeouts.kex.map(|timeout| (since + timeout, Expiry::Kex)),
+            Kex::Idle => None,
+        };
+        let auth = timeouts
+            .auth
+            .filter(|_| !authenticated)
+            .map(|timeout| (self.started + timeout, Expiry::Auth));
+        let idle = timeouts
+            .idle
+            .map(|timeout| (self.active + timeout, Expiry::Idle));
+
+        [kex, auth, idle]
+            .into_iter()
+            .flatten()
+            .min_by_key(|(at, _)| *at)
+    }
+
+    /// Disconnect the peer upon the `expiry` of a deadline.
+    async fn expire(&mut self, expiry: Expiry) -> DisconnectedError {
+        tracing::info!("Disconnecting peer: {}", expiry.description());
+
+        self.disconnect(expiry.reason(), expiry.description()).await
+    }
+
+    /// Initiate a key-exchange if the keys exceeded their [`side::Side::rekey_limit`].
+    async fn kexinit(&mut self) -> Result<()> {
+        if self.stream()?.should_rekey() {
+            self.rekey().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the packet to the peer, or queue it if a key-exchange is in progress,
+    /// unless it is a generic message, which are allowed at any time.
+    async fn transmit(&mut self, packet: Packet) -> Result<()> {
+        self.kexinit().await?;
+
+        if let Kex::Init(..) = self.kex
+            && !matches!(packet[0], 1..=4)
+        {
+            self.queue.push_back(packet);
+
+            Ok(())
+        } else {
+            self.stream()?.send(packet).await
+        }
+    }
+
+    /// Receive a packet from the peer and handle the **`SSH-TRANS`** layer messages,
+    /// returning it only if it is to be handled by the upper layers.
+    async fn process(&mut self) -> Result<Option<Packet>> {
+        self.kexinit().await?;
+
+        let deadline = self.deadline();
+        let stream = self.stream()?;
+        stream.set_deadline(deadline);
+
+        let packet = match stream.recv().await {
+            Err(Error::Timeout(expiry)) => return Err(self.expire(expiry).await.into()),
+            packet => packet?,
         };
+        self.active = Instant::now();
+
+        let stream = self.stream()?;
 
-        if stream.should_rekey()
-            && let Err(err) = self.config.kex(stream, &self.peer_id).await
+        if let Ok(peerkexinit) = packet.to::<KexInit>() {
+            self.kex(peerkexinit).await?;
+        } else if let Ok(Disconnect {
+            reason,
+            description,
+            ..
+        }) = packet.to()
         {
+            tracing::info!("Peer disconnected with `{reason:?}`: {description}");
+
+            self.shutdown(DisconnectedError {
+                by: DisconnectedBy::Them,
+                reason,
+                description,
+            });
+        } else if let Ok(Ignore { data }) = packet.to() {
+            tracing::debug!("Received an 'ignore' message with length {}", data.len());
+        } else if let Ok(Unimplemented { seq }) = packet.to() {
+            tracing::debug!("Received an 'unimplemented' message about packet #{seq}",);
+        } else if let Ok(Debug { message, .. }) = packet.to() {
+            tracing::debug!("Received a 'debug' message: {message}");
+        } else if stream.session_id().is_none() {
             return Err(self
-                .disconnect(DisconnectReason::KeyExchangeFailed, err.to_string())
+                .disconnect(
+                    DisconnectReason::ProtocolError,
+                    "Unexpected message during the initial key-exchange",
+                )
                 .await
                 .into());
+        } else if let Ok(extinfo) = packet.to::<ExtInfo>() {
+            tracing::debug!(
+                "Received an 'ext-info' message with {} extensions",
+                extinfo.extensions.len()
+            );
+
+            self.extensions.extend(extinfo);
+        } else if let Ok(Ping { data }) = packet.to() {
+            self.transmit((&Pong { data }).into_packet()).await?;
+        } else if let Ok(Pong { data }) = packet.to() {
+            tracing::debug!("Received a 'pong' message with length {}", data.len());
+        } else {
+            return Ok(Some(packet));
+        }
+
+        Ok(None)
+    }
+
+    /// Complete the key-exchange upon the reception of the peer's [`KexInit`],
+    /// whichever side initiated it, and flush the packets queued in the meantime.
+    async fn kex(&mut self, peerkexinit: KexInit<'_>) -> Result<()> {
+        if let Kex::Idle = self.kex {
+            let kexinit = self.config.kexinit();
+            self.stream()?.send(&kexinit).await?;
+
+            self.kex = Kex::Init(kexinit.into(), Instant::now());
+        }
+
+        let deadline = self.deadline();
+        let Kex::Init(kexinit, _) = std::mem::replace(&mut self.kex, Kex::Idle) else {
+            unreachable!("the key-exchange is initiated above");
+        };
+
+        let Either::Left(stream) = &mut self.stream else {
+            unreachable!("the stream is checked to be connected above");
+        };
+        stream.set_deadline(deadline);
+
+        match self
+            .config
+            .kex(stream, &self.peer_id, &kexinit, &peerkexinit)
+            .await
+        {
+            Ok(negociated) => self.negociated = Some(negociated),
+            Err(err) => {
+                return Err(match err {
+                    Error::Disconnected(err) => {
+                        tracing::info!(
+                            "Peer disconnected during key-exchange with `{:?}`: {}",
+                            err.reason,
+                            err.description
+                        );
+
+                        self.shutdown(err.clone());
+
+                        err
+                    }
+                    Error::Timeout(expiry) => self.expire(expiry).await,
+                    err => {
+                        self.disconnect(Self::kex_failure(&err), err.to_string())
+                            .await
+                    }
+                }
+                .into());
+            }
+        }
+
+        while let Some(packet) = self.queue.pop_front() {
+            let success = packet.to::<userauth::Success>().is_ok();
+
+            stream.send(packet).await?;
+
+            if success && std::mem::take(&mut self.authenticating) {
+                stream.set_authenticated();
+            }
         }
 
-        stream.send(message).await
+        Ok(())
+    }
+
+    /// The disconnect reason to report to the peer for a failed key-exchange.
+    fn kex_failure(err: &Error) -> DisconnectReason {
+        match err {
+            Error::HostKeyNotVerifiable => DisconnectReason::HostKeyNotVerifiable,
+            Error::UnexpectedMessage => DisconnectReason::ProtocolError,
+            _ => DisconnectReason::KeyExchangeFailed,
+        }
     }
 
     /// Send a _disconnect message_ to the peer and shutdown the session.
@@ -169,11 +495,20 @@ where
             reason: message.reason,
             description: message.description,
         };
-        self.stream = Either::Right(err.clone());
+        self.shutdown(err.clone());
 
         err
     }
 
+    /// Shutdown the stream after a disconnection, retaining it's final statistics.
+    fn shutdown(&mut self, err: DisconnectedError) {
+        if let Either::Left(stream) = &self.stream {
+            self.stats = stream.stats();
+        }
+
+        self.stream = Either::Right(err);
+    }
+
     /// Handle a _service_ for the peer.
     pub async fn handle<H>(mut self, mut service: H) -> Result<H::Ok<IO, S>, H::Err>
     where
@@ -0,0 +1,55 @@
+use ssh_key::{Certificate, PublicKey};
+
+use crate::stream::algorithm::{
+    cipher::Cipher,
+    compress::Compress,
+    hmac::Hmac,
+    kex::{Kex, KexMeta},
+    key::Key,
+};
+
+/// The algorithms negociated with the peer in a key-exchange,
+/// along with the host key presented by the _server_.
+#[derive(Debug, Clone)]
+pub struct Negociated {
+    /// The negociated _key-exchange_ algorithm.
+    pub kex: Kex,
+
+    /// The negociated _server host key_ algorithm.
+    pub key: Key,
+
+    /// The host key the _server_ signed the exchange with.
+    pub host_key: PublicKey,
+
+    /// The OpenSSH certificate of the host key, when presented by the _server_.
+    pub certificate: Option<Certificate>,
+
+    /// The algorithms negociated for the _client-to-server_ direction.
+    pub client_to_server: Directional,
+
+    /// The algorithms negociated for the _server-to-client_ direction.
+    pub server_to_client: Directional,
+}
+
+/// The algorithms negociated for one direction of the transport.
+#[derive(Debug, Clone)]
+pub struct Directional {
+    /// The negociated _encryption_ algorithm.
+    pub cipher: Cipher,
+
+    /// The negociated _hmac_ algorithm, which is `none` for authenticated ciphers.
+    pub hmac: Hmac,
+
+    /// The negociated _compression_ algorithm.
+    pub compress: Compress,
+}
+
+impl From<&KexMeta<'_>> for Directional {
+    fn from(meta: &KexMeta<'_>) -> Self {
+        Self {
+            cipher: meta.cipher.clone(),
+            hmac: meta.hmac.clone(),
+            compress: meta.compress.clone(),
+        }
+    }
+}
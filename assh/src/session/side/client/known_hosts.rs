@@ -0,0 +1,557 @@
+//! Support for the OpenSSH `known_hosts` file format, as a [`HostKeyVerifier`].
+//!
+//! see <https://man7.org/linux/man-pages/man8/sshd.8.html#SSH_KNOWN_HOSTS_FILE_FORMAT>.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use hmac::{KeyInit, Mac};
+use rand::Rng;
+use ssh_key::{
+    Certificate, PublicKey,
+    certificate::CertType,
+    known_hosts::{Entry, HostPatterns, Marker},
+};
+use ssh_packet::arch::id::Id;
+
+use super::{HostKeyVerifier, Verification};
+
+/// The policy regarding host keys missing from the known hosts,
+/// mirroring OpenSSH's `StrictHostKeyChecking` option.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// Never add host keys automatically, and refuse unknown or changed host keys.
+    Yes,
+
+    /// Add unknown host keys automatically, but refuse changed host keys.
+    #[default]
+    AcceptNew,
+
+    /// Add unknown host keys automatically, and accept changed host keys with a warning.
+    No,
+}
+
+/// The outcome of looking up a host key in the known hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The host key is known for this host.
+    Known,
+
+    /// The host key is marked as `@revoked` for this host.
+    Revoked,
+
+    /// The host is known with a different key of the same algorithm.
+    Changed,
+
+    /// The host isn't known with a key of this algorithm.
+    Unknown,
+
+    /// The host certificate is signed by a trusted authority, but isn't valid for this host.
+    Invalid,
+}
+
+/// A set of _known hosts_, optionally backed by a file where new host keys are appended.
+#[derive(Debug, Default)]
+pub struct KnownHosts {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<Entry>>,
+
+    policy: Policy,
+    hash: bool,
+}
+
+impl KnownHosts {
+    /// Parse the known hosts from the `known_hosts`-formatted `input`,
+    /// held in memory only.
+    pub fn parse(input: &str, policy: Policy) -> ssh_key::Result<Self> {
+        Ok(Self {
+            path: None,
+            entries: Mutex::new(
+                ssh_key::known_hosts::KnownHosts::new(input).collect::<Result<_, _>>()?,
+            ),
+            policy,
+            hash: false,
+        })
+    }
+
+    /// Read the known hosts from the file at `path`, where new host keys will be appended,
+    /// a missing file being treated as empty.
+    pub fn open(path: impl Into<PathBuf>, policy: Policy) -> ssh_key::Result<Self> {
+        let path = path.into();
+
+        let input = match std::fs::read_to_string(&path) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Default::default(),
+            input => input?,
+        };
+
+        Ok(Self {
+            path: Some(path),
+            ..Self::parse(&input, policy)?
+        })
+    }
+
+    /// The path of the user's known hosts file, `~/.ssh/known_hosts`.
+    pub fn user_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| Path::new(&home).join(".ssh").join("known_hosts"))
+    }
+
+    /// Whether the host names of appended entries are hashed, mirroring OpenSSH's `HashKnownHosts` option.
+    pub fn hashed(mut self, hash: bool) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Look up the host `key` for `host`:`port` in the known hosts.
+    pub fn check(&self, host: &str, port: u16, key: &PublicKey) -> Status {
+        let name = name(host, port);
+        let entries = self.entries();
+
+        let matching = entries
+            .iter()
+            .filter(|entry| matches(entry.host_patterns(), &name));
+
+        // A revoked key is refused regardless of any other entry.
+        if matching
+            .clone()
+            .any(|entry| entry.marker() == Some(&Marker::Revoked) && same(entry.public_key(), key))
+        {
+            return Status::Revoked;
+        }
+
+        let mut status = Status::Unknown;
+        for entry in matching.filter(|entry| entry.marker().is_none()) {
+            if same(entry.public_key(), key) {
+                return Status::Known;
+            } else if entry.public_key().algorithm() == key.algorithm() {
+                status = Status::Changed;
+            }
+        }
+
+        status
+    }
+
+    /// Get the keys of the certificate authorities trusted for `host`:`port`,
+    /// from the `@cert-authority` entries.
+    pub fn authorities(&self, host: &str, port: u16) -> Vec<PublicKey> {
+        let name = name(host, port);
+
+        self.entries()
+            .iter()
+            .filter(|entry| entry.marker() == Some(&Marker::CertAuthority))
+            .filter(|entry| matches(entry.host_patterns(), &name))
+            .map(|entry| entry.public_key().clone())
+            .collect()
+    }
+
+    /// Validate the host `certificate` for `host`:`port` against the trusted certificate authorities.
+    ///
+    /// The certificate is [`Status::Known`] when it's a host certificate signed by one of the
+    /// authorities, within it's validity period, without any critical option, and listing `host`
+    /// among it's principals if it restricts them; it is [`Status::Revoked`] when either
+    /// the certified key or the authority is marked as `@revoked`.
+    pub fn validate(&self, host: &str, port: u16, certificate: &Certificate) -> Status {
+        let authorities = self.authorities(host, port);
+        let Some(authority) = authorities
+            .iter()
+            .find(|authority| authority.key_data() == certificate.signature_key())
+        else {
+            return Status::Unknown;
+        };
+
+        let key = PublicKey::from(certificate.public_key().clone());
+        if self.check(host, port, &key) == Status::Revoked
+            || self.check(host, port, authority) == Status::Revoked
+        {
+            return Status::Revoked;
+        }
+
+        let principals = certificate.valid_principals();
+        let valid = certificate.cert_type() == CertType::Host
+            && certificate
+                .validate([&authority.fingerprint(ssh_key::HashAlg::Sha256)])
+                .is_ok()
+            && certificate.critical_options().is_empty()
+            && (principals.is_empty()
+                || principals
+                    .iter()
+                    .any(|principal| principal.eq_ignore_ascii_case(host)));
+
+        if valid {
+            Status::Known
+        } else {
+            Status::Invalid
+        }
+    }
+
+    /// Add the host `key` for `host`:`port` to the known hosts,
+    /// appending it to the backing file if any.
+    pub fn append(&self, host: &str, port: u16, key: &PublicKey) -> ssh_key::Result<()> {
+        let name = name(host, port);
+
+        let patterns = if self.hash {
+            let mut salt = vec![0; 20];
+            rand::rng().fill_bytes(&mut salt);
+
+            HostPatterns::HashedName {
+                hash: hash(&salt, &name),
+                salt,
+            }
+        } else {
+            HostPatterns::Patterns(vec![name])
+        };
+
+        let mut key = key.clone();
+        key.set_comment("");
+
+        let line = format!("{} {}", patterns.to_string(), key.to_openssh()?);
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+
+            writeln!(file, "{line}")?;
+        }
+
+        self.entries().push(line.parse()?);
+
+        Ok(())
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Vec<Entry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl HostKeyVerifier for KnownHosts {
+    fn verify<'a>(
+        &'a self,
+        _id: &'a Id,
+        host: &'a str,
+        port: u16,
+        key: &'a PublicKey,
+    ) -> Verification<'a> {
+        let fingerprint = key.fingerprint(Default::default());
+
+        let trusted = match (self.check(host, port, key), self.policy) {
+            (Status::Known, _) => true,
+            (Status::Revoked, _) => {
+                tracing::warn!("Host key `{fingerprint}` for `{host}:{port}` has been revoked");
+
+                false
+            }
+            (Status::Changed, Policy::No) => {
+                tracing::warn!(
+                    "Host key for `{host}:{port}` has changed to `{fingerprint}`, accepting it anyway"
+                );
+
+                true
+            }
+            (Status::Changed, _) => {
+                tracing::warn!("Host key for `{host}:{port}` has changed to `{fingerprint}`");
+
+                false
+            }
+            (Status::Unknown, Policy::Yes) => {
+                tracing::warn!("Host key `{fingerprint}` for `{host}:{port}` is unknown");
+
+                false
+            }
+            (Status::Invalid, _) => {
+                tracing::warn!("Host key `{fingerprint}` for `{host}:{port}` is invalid");
+
+                false
+            }
+            (Status::Unknown, Policy::AcceptNew | Policy::No) => {
+                tracing::info!(
+                    "Adding host key `{fingerprint}` for `{host}:{port}` to the known hosts"
+                );
+
+                if let Err(err) = self.append(host, port, key) {
+                    tracing::warn!("Unable to add the host key to the known hosts: {err}");
+                }
+
+                true
+            }
+        };
+
+        Box::pin(async move { trusted })
+    }
+
+    fn verify_certificate<'a>(
+        &'a self,
+        id: &'a Id,
+        host: &'a str,
+        port: u16,
+        certificate: &'a Certificate,
+    ) -> Verification<'a> {
+        let authority = certificate.signature_key().fingerprint(Default::default());
+
+        let trusted = match self.validate(host, port, certificate) {
+            Status::Known => true,
+            Status::Revoked => {
+                tracing::warn!(
+                    "Host certificate for `{host}:{port}` signed by `{authority}` has been revoked"
+                );
+
+                false
+            }
+            Status::Invalid => {
+                tracing::warn!(
+                    "Host certificate for `{host}:{port}` signed by `{authority}` is invalid"
+                );
+
+                false
+            }
+            Status::Unknown | Status::Changed => {
+                // Like OpenSSH, fall back to the certified key when the authority isn't trusted.
+                tracing::debug!(
+                    "Host certificate authority `{authority}` isn't trusted for `{host}:{port}`, verifying the certified key"
+                );
+
+                return Box::pin(async move {
+                    let key = PublicKey::from(certificate.public_key().clone());
+
+                    self.verify(id, host, port, &key).await
+                });
+            }
+        };
+
+        Box::pin(async move { trusted })
+    }
+}
+
+/// The name under which `host`:`port` is looked up, bracketed with the port when it's not the default.
+fn name(host: &str, port: u16) -> String {
+    let host = host.to_lowercase();
+
+    if port == 22 {
+        host
+    } else {
+        format!("[{host}]:{port}")
+    }
+}
+
+/// Compare the public keys, ignoring their comments.
+fn same(a: &PublicKey, b: &PublicKey) -> bool {
+    a.key_data() == b.key_data()
+}
+
+/// The salted HMAC-SHA1 of a host name for hashed entries.
+fn hash(salt: &[u8], name: &str) -> [u8; 20] {
+    let mut mac =
+        hmac::Hmac::<sha1::Sha1>::new_from_slice(salt).expect("hmac accepts any key size");
+    mac.update(name.as_bytes());
+
+    mac.finalize().into_bytes().into()
+}
+
+/// Whether the `name` matches the patterns, where a single matching negated pattern
+/// prevents the match regardless of the other patterns.
+fn matches(patterns: &HostPatterns, name: &str) -> bool {
+    match patterns {
+        HostPatterns::Patterns(patterns) => {
+            let mut matched = false;
+
+            for pattern in patterns {
+                match pattern.strip_prefix('!') {
+                    Some(pattern) if glob(&pattern.to_lowercase(), name) => return false,
+                    Some(_) => (),
+                    None => matched |= glob(&pattern.to_lowercase(), name),
+                }
+            }
+
+            matched
+        }
+        HostPatterns::HashedName { salt, hash: digest } => hash(salt, name) == *digest,
+    }
+}
+
+/// Match the `name` against the `pattern`, where `*` matches any sequence of characters and `?` any single character.
+fn glob(pattern: &str, name: &str) -> bool {
+    fn glob(pattern: &[u8], name: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some((b'*', rest)) => (0..=name.len()).any(|skip| glob(rest, &name[skip..])),
+            Some((b'?', rest)) => !name.is_empty() && glob(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && glob(rest, &name[1..]),
+        }
+    }
+
+    glob(pattern.as_bytes(), name.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY1: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIPgJ46Pb6H9RO4ZL8ffO/cAeJQCgBR6D9HZLonvNNFU5";
+    const KEY2: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIIbMPPyY1S0/L5t3C7bEAEQ8IXVRLnko+bQz69ftYaIr";
+
+    fn key(key: &str) -> PublicKey {
+        key.parse().expect("Unable to parse the public key")
+    }
+
+    #[test]
+    fn it_matches_plain_and_wildcard_patterns() {
+        let hosts = KnownHosts::parse(
+            &format!(
+                "example.com,[example.com]:2222 {KEY1}\n\
+                 *.example.org,!secret.example.org {KEY2}\n\
+                 @revoked * {KEY2}\n\
+                 @cert-authority *.example.net {KEY1}\n"
+            ),
+            Policy::Yes,
+        )
+        .expect("Unable to parse the known hosts");
+
+        assert_eq!(hosts.check("example.com", 22, &key(KEY1)), Status::Known);
+        assert_eq!(hosts.check("EXAMPLE.com", 2222, &key(KEY1)), Status::Known);
+        assert_eq!(
+            hosts.check("example.com", 2200, &key(KEY1)),
+            Status::Unknown
+        );
+        assert_eq!(
+            hosts.check("www.example.org", 22, &key(KEY1)),
+            Status::Changed
+        );
+        assert_eq!(
+            hosts.check("secret.example.org", 22, &key(KEY1)),
+            Status::Unknown
+        );
+        assert_eq!(hosts.check("example.com", 22, &key(KEY2)), Status::Revoked);
+        assert_eq!(
+            hosts.check("www.example.net", 22, &key(KEY1)),
+            Status::Unknown
+        );
+        assert_eq!(hosts.authorities("www.example.net", 22), vec![key(KEY1)]);
+    }
+
+    #[test]
+    fn it_validates_host_certificates() {
+        let random = || {
+            ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)
+                .expect("Unable to generate a random key")
+        };
+        let (ca, revoked) = (random(), random());
+
+        let certify = |key: &ssh_key::PrivateKey, cert_type, principal: &str| {
+            let mut builder = ssh_key::certificate::Builder::new_with_random_nonce(
+                &mut rand::rng(),
+                key.public_key().key_data().clone(),
+                0,
+                u64::MAX,
+            )
+            .expect("Unable to create the certificate");
+            builder
+                .cert_type(cert_type)
+                .expect("Unable to set the certificate type");
+            builder
+                .valid_principal(principal)
+                .expect("Unable to set the principal");
+
+            builder.sign(&ca).expect("Unable to sign the certificate")
+        };
+
+        let hosts = KnownHosts::parse(
+            &format!(
+                "@cert-authority *.example.com {}\n\
+                 @revoked * {}\n",
+                ca.public_key()
+                    .to_openssh()
+                    .expect("Unable to encode the key"),
+                revoked
+                    .public_key()
+                    .to_openssh()
+                    .expect("Unable to encode the key"),
+            ),
+            Policy::Yes,
+        )
+        .expect("Unable to parse the known hosts");
+
+        let certificate = certify(&random(), CertType::Host, "www.example.com");
+        assert_eq!(
+            hosts.validate("www.example.com", 22, &certificate),
+            Status::Known
+        );
+        assert_eq!(
+            hosts.validate("ftp.example.com", 22, &certificate),
+            Status::Invalid
+        );
+        assert_eq!(
+            hosts.validate("www.example.org", 22, &certificate),
+            Status::Unknown
+        );
+        assert_eq!(
+            hosts.validate(
+                "www.example.com",
+                22,
+                &certify(&random(), CertType::User, "www.example.com")
+            ),
+            Status::Invalid
+        );
+        assert_eq!(
+            hosts.validate(
+                "www.example.com",
+                22,
+                &certify(&revoked, CertType::Host, "www.example.com")
+            ),
+            Status::Revoked
+        );
+    }
+
+    #[test]
+    fn it_matches_hashed_host_names() {
+        // Hashed with `ssh-keygen -H`.
+        let hosts = KnownHosts::parse(
+            &format!(
+                "|1|++xZlIjcDUc0I9HU2aQPW+MxkZA=|a1NiUK2ha+8k6Fce10AGi8OR3zM= {KEY1}\n\
+                 |1|mHSjzfJklV1L1P1k5lD2zJMNkdg=|3IpUJvocGjFqSEIGCG4I2d6MIIY= {KEY2}\n"
+            ),
+            Policy::Yes,
+        )
+        .expect("Unable to parse the known hosts");
+
+        assert_eq!(hosts.check("example.com", 22, &key(KEY1)), Status::Known);
+        assert_eq!(hosts.check("example.com", 2222, &key(KEY2)), Status::Known);
+        assert_eq!(hosts.check("example.org", 22, &key(KEY1)), Status::Unknown);
+    }
+
+    #[test]
+    fn it_appends_new_entries() {
+        let path = std::env::temp_dir().join(format!("assh-known-hosts-{}", rand::random::<u64>()));
+
+        let hosts = KnownHosts::open(&path, Policy::AcceptNew)
+            .expect("Unable to open the known hosts")
+            .hashed(true);
+        hosts
+            .append("example.com", 2222, &key(KEY1))
+            .expect("Unable to append to the known hosts");
+
+        let reopened =
+            KnownHosts::open(&path, Policy::Yes).expect("Unable to reopen the known hosts");
+        std::fs::remove_file(&path).expect("Unable to remove the known hosts");
+
+        assert_eq!(hosts.check("example.com", 2222, &key(KEY1)), Status::Known);
+        assert_eq!(
+            reopened.check("example.com", 2222, &key(KEY1)),
+            Status::Known
+        );
+        assert_eq!(
+            reopened.check("example.com", 22, &key(KEY1)),
+            Status::Unknown
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Client-[`Side`] implementation of the _session_.
+
+use std::sync::Arc;
+
+use rand::Rng;
+use ssh_packet::{arch::NameList, trans::KexInit};
+
+use super::{Side, server::Server};
+use crate::session::{Negociated, extensions::ExtInfo};
+use crate::{
+    Error, Pipe, Result,
+    error::ListError,
+    stream::{
+        Stream, Transport,
+        algorithm::{
+            Negociate,
+            cipher::Cipher,
+            compress::Compress,
+            hmac::Hmac,
+            kex::{self, Kex, KexMeta},
+            key::{self, Key},
+            list,
+        },
+    },
+};
+
+mod verifier;
+pub use verifier::{AcceptAll, HostKeyVerifier, Pinned, TrustOnFirstUse, Verification};
+
+pub mod known_hosts;
+
+pub use crate::stream::{RekeyLimit, Timeouts};
+
+#[doc(no_inline)]
+pub use ssh_key::PublicKey;
+#[doc(no_inline)]
+pub use ssh_packet::arch::id::Id;
+
+/// A _client_-side session configuration.
+///
+/// With the `serde` feature, the configuration can be deserialized, with the [`Id`]
+/// in it's textual form and the algorithms in the OpenSSH list syntax
+/// of [`algorithm::alter`](crate::algorithm::alter), except for the `verifier`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Client {
+    /// [`Id`] for this _client_ session.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "super::de::id"))]
+    pub id: Id,
+
+    /// Name of the remote host, as passed to the `verifier`.
+    pub host: String,
+
+    /// Port of the remote host, as passed to the `verifier`.
+    pub port: u16,
+
+    /// Verifier of the host key presented by the _server_,
+    /// which defaults to [`AcceptAll`] and should be replaced for any real use.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub verifier: Arc<dyn HostKeyVerifier>,
+
+    /// Limits of usage of the session keys before a re-exchange.
+    pub rekey_limit: RekeyLimit,
+
+    /// Deadlines of the session before disconnecting the _server_.
+    pub timeouts: Timeouts,
+
+    /// The algorithms enabled for this _client_ session.
+    pub algorithms: Algorithms,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self {
+            id: Id::v2(
+                concat!(
+                    env!("CARGO_PKG_NAME"),
+                    "@client:",
+                    env!("CARGO_PKG_VERSION")
+                ),
+                None::<&str>,
+            ),
+            host: Default::default(),
+            port: 22,
+            verifier: Arc::new(AcceptAll),
+            rekey_limit: Default::default(),
+            timeouts: Default::default(),
+            algorithms: Default::default(),
+        }
+    }
+}
+
+/// Algorithms for a _client_-side session.
+#[derive(Debug, Clone)]
+pub struct Algorithms {
+    /// Enabled algorithms for _key-exchange_.
+    pub kexs: Vec<Kex>,
+
+    /// Enabled algorithms for _server key signature_,
+    /// each also advertised first as it's OpenSSH certificate type.
+    pub keys: Vec<Key>,
+
+    /// Enabled algorithms for _encryption & decryption_.
+    pub ciphers: Vec<Cipher>,
+
+    /// Enabled algorithms for _hmac_.
+    pub macs: Vec<Hmac>,
+
+    /// Enabled algorithms for _compression_.
+    pub compressions: Vec<Compress>,
+}
+
+impl Default for Algorithms {
+    fn default() -> Self {
+        Self::compatible()
+    }
+}
+
+impl Algorithms {
+    /// Only the state-of-the-art algorithms,
+    /// see [`server::Algorithms::modern`](super::server::Algorithms::modern),
+    /// with Ed25519 and ECDSA _server_ keys.
+    pub fn modern() -> Self {
+        Self::with_keys(
+            super::server::Algorithms::modern(),
+            vec![
+                Key::Ed25519,
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP384,
+                },
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP256,
+                },
+            ],
+        )
+    }
+
+    /// The secure algorithms widely supported by the peers, which is the default,
+    /// see [`server::Algorithms::compatible`](super::server::Algorithms::compatible),
+    /// with Ed25519, ECDSA and RSA with sha-2 _server_ keys.
+    pub fn compatible() -> Self {
+        Self::with_keys(
+            super::server::Algorithms::compatible(),
+            vec![
+                Key::Ed25519,
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP384,
+                },
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP256,
+                },
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha512),
+                },
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha256),
+                },
+            ],
+        )
+    }
+
+    /// Only the algorithms approved by FIPS 140-3,
+    /// see [`server::Algorithms::fips`](super::server::Algorithms::fips),
+    /// with ECDSA and RSA with sha-2 _server_ keys.
+    pub fn fips() -> Self {
+        Self::with_keys(
+            super::server::Algorithms::fips(),
+            vec![
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP384,
+                },
+                Key::Ecdsa {
+                    curve: ssh_key::EcdsaCurve::NistP256,
+                },
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha512),
+                },
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha256),
+                },
+            ],
+        )
+    }
+
+    /// Alter the algorithms with an OpenSSH configuration option, such as `Ciphers -aes*-ctr`,
+    /// with the list syntax of [`algorithm::alter`](crate::algorithm::alter).
+    ///
+    /// The supported options are `KexAlgorithms`, `HostKeyAlgorithms`, `Ciphers`, `MACs`
+    /// and `Compression`, which also accepts `yes`, `delayed` and `no`.
+    pub fn apply(&mut self, option: &str) -> Result<(), ListError> {
+        let (keyword, list) = list::option(option)?;
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "kexalgorithms" => self.kexs = list::alter(&self.kexs, list)?,
+            "hostkeyalgorithms" => self.keys = list::alter(&self.keys, list)?,
+            "ciphers" => self.ciphers = list::alter(&self.ciphers, list)?,
+            "macs" => self.macs = list::alter(&self.macs, list)?,
+            "compression" => self.compressions = list::compressions(&self.compressions, list)?,
+            _ => return Err(ListError::Option(keyword.into())),
+        }
+
+        Ok(())
+    }
+
+    /// The [`Self::compatible`] algorithms, followed by the weak ones
+    /// to interoperate with legacy peers, including RSA with sha-1 and DSA _server_ keys.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+    pub fn legacy() -> Self {
+        let mut algorithms = Self::with_keys(super::server::Algorithms::legacy(), Vec::new());
+
+        algorithms.keys = Self::compatible().keys;
+        algorithms.keys.extend([Key::Rsa { hash: None }, Key::Dsa]);
+
+        algorithms
+    }
+
+    fn with_keys(algorithms: super::server::Algorithms, keys: Vec<Key>) -> Self {
+        let super::server::Algorithms {
+            kexs,
+            ciphers,
+            macs,
+            compressions,
+        } = algorithms;
+
+        Self {
+            kexs,
+            keys,
+            ciphers,
+            macs,
+            compressions,
+        }
+    }
+}
+
+impl Side for Client {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn rekey_limit(&self) -> &RekeyLimit {
+        &self.rekey_limit
+    }
+
+    fn timeouts(&self) -> &Timeouts {
+        &self.timeouts
+    }
+
+    fn announced_keys(&self) -> impl Iterator<Item = &PublicKey> {
+        std::iter::empty()
+    }
+
+    fn kexinit(&self) -> KexInit<'static> {
+        let mut cookie = [0u8; 16];
+        rand::rng().fill_bytes(&mut cookie);
+
+        KexInit {
+            cookie,
+            kex_algorithms: NameList::from_iter(
+                self.algorithms
+                    .kexs
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .chain([kex::EXT_INFO_C, kex::KEX_STRICT_C]),
+            ),
+            server_host_key_algorithms: NameList::from_iter(
+                self.algorithms
+                    .keys
+                    .iter()
+                    .map(Key::to_certificate_type)
+                    .chain(self.algorithms.keys.iter().map(ToString::to_string)),
+            ),
+            encryption_algorithms_client_to_server: NameList::from_iter(&self.algorithms.ciphers),
+            encryption_algorithms_server_to_client: NameList::from_iter(&self.algorithms.ciphers),
+            mac_algorithms_client_to_server: NameList::from_iter(&self.algorithms.macs),
+            mac_algorithms_server_to_client: NameList::from_iter(&self.algorithms.macs),
+            compression_algorithms_client_to_server: NameList::from_iter(
+                &self.algorithms.compressions,
+            ),
+            compression_algorithms_server_to_client: NameList::from_iter(
+                &self.algorithms.compressions,
+            ),
+            languages_client_to_server: Default::default(),
+            languages_server_to_client: Default::default(),
+            first_kex_packet_follows: false.into(),
+        }
+    }
+
+    fn extinfo(&self, _peerkexinit: &KexInit) -> Option<ExtInfo> {
+        None
+    }
+
+    fn is_strict(&self, peerkexinit: &KexInit) -> bool {
+        peerkexinit
+            .kex_algorithms
+            .into_iter()
+            .any(|name| &*name == kex::KEX_STRICT_S)
+    }
+
+    async fn exchange(
+        &self,
+        stream: &mut Stream<impl Pipe>,
+        kexinit: &KexInit<'_>,
+        peerkexinit: &KexInit<'_>,
+        peer_id: &Id,
+    ) -> Result<(Transport, Negociated)> {
+        let client = KexMeta::new::<Client>(self.id(), kexinit, peerkexinit)?;
+        let server = KexMeta::new::<Server>(peer_id, kexinit, peerkexinit)?;
+        let (client_to_server, server_to_client) = ((&client).into(), (&server).into());
+
+        let kex = Kex::negociate(kexinit, peerkexinit)?;
+        // The key-exchange state is held on the heap, being too large for the stack in debug builds.
+        let (transport, host_key) = Box::pin(kex.as_client(stream, client, server)).await?;
+
+        let trusted = match &host_key.certificate {
+            Some(certificate) => {
+                self.verifier
+                    .verify_certificate(peer_id, &self.host, self.port, certificate)
+                    .await
+            }
+            None => {
+                self.verifier
+                    .verify(peer_id, &self.host, self.port, &host_key.key)
+                    .await
+            }
+        };
+        if !trusted {
+            tracing::warn!(
+                "Host key `{}` for `{}:{}` was rejected by the verifier",
+                host_key.key.fingerprint(Default::default()),
+                self.host,
+                self.port
+            );
+
+            return Err(Error::HostKeyNotVerifiable);
+        }
+
+        let negociated = Negociated {
+            kex,
+            key: key::negociate(kexinit, peerkexinit)?,
+            host_key: host_key.key,
+            certificate: host_key.certificate,
+            client_to_server,
+            server_to_client,
+        };
+
+        Ok((transport, negociated))
+    }
+}
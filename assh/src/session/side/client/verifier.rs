@@ -0,0 +1,172 @@
+//! Verifiers of the _host key_ presented by the _server_ in the key-exchange.
+
+use std::{collections::HashMap, pin::Pin, sync::Mutex};
+
+use futures::Future;
+use ssh_key::{Certificate, Fingerprint, PublicKey};
+use ssh_packet::arch::id::Id;
+
+/// The future returned by [`HostKeyVerifier::verify`].
+pub type Verification<'a> = Pin<Box<dyn Future<Output = bool> + Send + Sync + 'a>>;
+
+/// A _client_-side verifier of the _host key_ presented by the _server_,
+/// deciding whether the key is to be trusted for the session to continue.
+pub trait HostKeyVerifier: std::fmt::Debug + Send + Sync {
+    /// Verify the host `key` of the peer identified by `id` and reached at `host`:`port`,
+    /// resolving to `true` to trust the key or `false` to abort the session.
+    fn verify<'a>(
+        &'a self,
+        id: &'a Id,
+        host: &'a str,
+        port: u16,
+        key: &'a PublicKey,
+    ) -> Verification<'a>;
+
+    /// Verify the host `certificate` presented by the peer identified by `id`
+    /// and reached at `host`:`port`, resolving to `true` to trust it or `false` to abort the session.
+    ///
+    /// The default implementation disregards the certificate, verifying the certified key with [`Self::verify`].
+    fn verify_certificate<'a>(
+        &'a self,
+        id: &'a Id,
+        host: &'a str,
+        port: u16,
+        certificate: &'a Certificate,
+    ) -> Verification<'a> {
+        Box::pin(async move {
+            let key = PublicKey::from(certificate.public_key().clone());
+
+            self.verify(id, host, port, &key).await
+        })
+    }
+}
+
+/// A [`HostKeyVerifier`] accepting any host key, which provides **no protection**
+/// against _man-in-the-middle_ attacks.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptAll;
+
+impl HostKeyVerifier for AcceptAll {
+    fn verify<'a>(
+        &'a self,
+        _id: &'a Id,
+        host: &'a str,
+        port: u16,
+        key: &'a PublicKey,
+    ) -> Verification<'a> {
+        tracing::debug!(
+            "Accepting host key `{}` for `{host}:{port}` without verification",
+            key.fingerprint(Default::default())
+        );
+
+        Box::pin(async { true })
+    }
+}
+
+/// A [`HostKeyVerifier`] accepting only the host keys matching one of the pinned fingerprints.
+#[derive(Debug, Clone, Default)]
+pub struct Pinned {
+    fingerprints: Vec<Fingerprint>,
+}
+
+impl Pinned {
+    /// Create a verifier from the set of trusted `fingerprints`.
+    pub fn new(fingerprints: impl IntoIterator<Item = Fingerprint>) -> Self {
+        Self {
+            fingerprints: fingerprints.into_iter().collect(),
+        }
+    }
+}
+
+impl HostKeyVerifier for Pinned {
+    fn verify<'a>(
+        &'a self,
+        _id: &'a Id,
+        _host: &'a str,
+        _port: u16,
+        key: &'a PublicKey,
+    ) -> Verification<'a> {
+        let trusted = self
+            .fingerprints
+            .iter()
+            .any(|fingerprint| key.fingerprint(fingerprint.algorithm()) == *fingerprint);
+
+        Box::pin(async move { trusted })
+    }
+}
+
+/// A [`HostKeyVerifier`] trusting the first host key seen for each `host`:`port`,
+/// and rejecting any subsequent host key that differs from it.
+///
+/// The trusted keys are only held in memory, for the lifetime of the verifier.
+#[derive(Debug, Default)]
+pub struct TrustOnFirstUse {
+    known: Mutex<HashMap<(String, u16), PublicKey>>,
+}
+
+impl HostKeyVerifier for TrustOnFirstUse {
+    fn verify<'a>(
+        &'a self,
+        _id: &'a Id,
+        host: &'a str,
+        port: u16,
+        key: &'a PublicKey,
+    ) -> Verification<'a> {
+        let mut known = self
+            .known
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let trusted = match known.get(&(host.to_string(), port)) {
+            Some(known) => known.key_data() == key.key_data(),
+            None => {
+                tracing::info!(
+                    "Trusting host key `{}` for `{host}:{port}` on first use",
+                    key.fingerprint(Default::default())
+                );
+
+                known.insert((host.to_string(), port), key.clone());
+
+                true
+            }
+        };
+
+        Box::pin(async move { trusted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> PublicKey {
+        ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)
+            .expect("Unable to generate a random key")
+            .public_key()
+            .clone()
+    }
+
+    #[async_std::test]
+    async fn pinned_accepts_only_the_pinned_keys() {
+        let id = Id::v2("peer", None::<&str>);
+        let (trusted, untrusted) = (key(), key());
+
+        let verifier = Pinned::new([trusted.fingerprint(ssh_key::HashAlg::Sha512)]);
+
+        assert!(verifier.verify(&id, "host", 22, &trusted).await);
+        assert!(!verifier.verify(&id, "host", 22, &untrusted).await);
+    }
+
+    #[async_std::test]
+    async fn trust_on_first_use_rejects_a_changed_key() {
+        let id = Id::v2("peer", None::<&str>);
+        let (first, second) = (key(), key());
+
+        let verifier = TrustOnFirstUse::default();
+
+        assert!(verifier.verify(&id, "host", 22, &first).await);
+        assert!(verifier.verify(&id, "host", 22, &first).await);
+        assert!(!verifier.verify(&id, "host", 22, &second).await);
+        assert!(verifier.verify(&id, "host", 2222, &second).await);
+    }
+}
@@ -0,0 +1,319 @@
+//! Deserialization of the [`Client`](super::Client) and [`Server`](super::Server) configurations,
+//! with the algorithm lists in the OpenSSH syntax.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Deserializer, de::Error as _};
+use ssh_key::{Certificate, PrivateKey};
+use ssh_packet::arch::id::Id;
+
+use super::{client, server};
+use crate::stream::{
+    Timeouts,
+    algorithm::{
+        compress::Compress,
+        kex::moduli::{Moduli, ModuliFile},
+        key::Key,
+        list::{self, Named},
+    },
+};
+
+/// An algorithm list, either in the OpenSSH syntax or as a sequence of names.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum List {
+    Syntax(String),
+    Names(Vec<String>),
+}
+
+impl List {
+    fn alter<T: Named, E: serde::de::Error>(self, base: &[T]) -> Result<Vec<T>, E> {
+        match self {
+            Self::Syntax(list) => list::alter(base, &list),
+            Self::Names(names) => list::alter(base, &names.join(",")),
+        }
+        .map_err(E::custom)
+    }
+
+    fn compressions<E: serde::de::Error>(self, base: &[Compress]) -> Result<Vec<Compress>, E> {
+        match self {
+            Self::Syntax(list) => list::compressions(base, &list).map_err(E::custom),
+            names => names.alter(base),
+        }
+    }
+}
+
+/// A named preset of algorithms, on top of which the lists are altered.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum Profile {
+    Modern,
+    #[default]
+    Compatible,
+    Fips,
+    #[cfg(feature = "insecure")]
+    Legacy,
+}
+
+impl<'de> Deserialize<'de> for server::Algorithms {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Lists {
+            #[serde(default)]
+            profile: Profile,
+            kexs: Option<List>,
+            ciphers: Option<List>,
+            macs: Option<List>,
+            compressions: Option<List>,
+        }
+
+        let lists = Lists::deserialize(deserializer)?;
+        let mut algorithms = match lists.profile {
+            Profile::Modern => Self::modern(),
+            Profile::Compatible => Self::compatible(),
+            Profile::Fips => Self::fips(),
+            #[cfg(feature = "insecure")]
+            Profile::Legacy => Self::legacy(),
+        };
+
+        if let Some(list) = lists.kexs {
+            algorithms.kexs = list.alter(&algorithms.kexs)?;
+        }
+        if let Some(list) = lists.ciphers {
+            algorithms.ciphers = list.alter(&algorithms.ciphers)?;
+        }
+        if let Some(list) = lists.macs {
+            algorithms.macs = list.alter(&algorithms.macs)?;
+        }
+        if let Some(list) = lists.compressions {
+            algorithms.compressions = list.compressions(&algorithms.compressions)?;
+        }
+
+        Ok(algorithms)
+    }
+}
+
+impl<'de> Deserialize<'de> for client::Algorithms {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Lists {
+            #[serde(default)]
+            profile: Profile,
+            kexs: Option<List>,
+            keys: Option<List>,
+            ciphers: Option<List>,
+            macs: Option<List>,
+            compressions: Option<List>,
+        }
+
+        let lists = Lists::deserialize(deserializer)?;
+        let mut algorithms = match lists.profile {
+            Profile::Modern => Self::modern(),
+            Profile::Compatible => Self::compatible(),
+            Profile::Fips => Self::fips(),
+            #[cfg(feature = "insecure")]
+            Profile::Legacy => Self::legacy(),
+        };
+
+        if let Some(list) = lists.kexs {
+            algorithms.kexs = list.alter(&algorithms.kexs)?;
+        }
+        if let Some(list) = lists.keys {
+            algorithms.keys = list.alter(&algorithms.keys)?;
+        }
+        if let Some(list) = lists.ciphers {
+            algorithms.ciphers = list.alter(&algorithms.ciphers)?;
+        }
+        if let Some(list) = lists.macs {
+            algorithms.macs = list.alter(&algorithms.macs)?;
+        }
+        if let Some(list) = lists.compressions {
+            algorithms.compressions = list.compressions(&algorithms.compressions)?;
+        }
+
+        Ok(algorithms)
+    }
+}
+
+/// Deserialize an [`Id`] from it's textual form, like `SSH-2.0-software comments`.
+pub fn id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Id, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+/// Deserialize the keys from the paths of OpenSSH private key files, like OpenSSH's `HostKey`.
+pub fn keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<PrivateKey>, D::Error> {
+    Vec::<PathBuf>::deserialize(deserializer)?
+        .into_iter()
+        .map(|path| {
+            PrivateKey::read_openssh_file(&path)
+                .map_err(|err| D::Error::custom(format!("{}: {err}", path.display())))
+        })
+        .collect()
+}
+
+/// Deserialize the certificates from the paths of OpenSSH certificate files, like OpenSSH's `HostCertificate`.
+pub fn certificates<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Certificate>, D::Error> {
+    Vec::<PathBuf>::deserialize(deserializer)?
+        .into_iter()
+        .map(|path| {
+            Certificate::read_file(&path)
+                .map_err(|err| D::Error::custom(format!("{}: {err}", path.display())))
+        })
+        .collect()
+}
+
+/// Deserialize the signature algorithms as a list altering the default ones.
+pub fn signature_algorithms<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Key>, D::Error> {
+    List::deserialize(deserializer)?.alter(&server::Server::default_signature_algorithms())
+}
+
+/// Deserialize a [`ModuliFile`] from it's path.
+pub fn moduli<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<dyn Moduli>, D::Error> {
+    let path = PathBuf::deserialize(deserializer)?;
+
+    ModuliFile::open(&path)
+        .map(|moduli| Arc::new(moduli) as Arc<dyn Moduli>)
+        .map_err(|err| D::Error::custom(format!("{}: {err}", path.display())))
+}
+
+/// Deserialize the _server_ [`Timeouts`], keeping the _server_ defaults for the missing ones.
+pub fn server_timeouts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timeouts, D::Error> {
+    /// Distinguish a missing duration from an explicitly disabled one.
+    fn present<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Option<Duration>>, D::Error> {
+        humantime_serde::deserialize(deserializer).map(Some)
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Partial {
+        #[serde(default, deserialize_with = "present")]
+        id: Option<Option<Duration>>,
+        #[serde(default, deserialize_with = "present")]
+        kex: Option<Option<Duration>>,
+        #[serde(default, deserialize_with = "present")]
+        auth: Option<Option<Duration>>,
+        #[serde(default, deserialize_with = "present")]
+        idle: Option<Option<Duration>>,
+    }
+
+    let partial = Partial::deserialize(deserializer)?;
+    let defaults = server::Server::default_timeouts();
+
+    Ok(Timeouts {
+        id: partial.id.unwrap_or(defaults.id),
+        kex: partial.kex.unwrap_or(defaults.kex),
+        auth: partial.auth.unwrap_or(defaults.auth),
+        idle: partial.idle.unwrap_or(defaults.idle),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        algorithm::{Cipher, Compress, Kex, Key},
+        side::{client::Client, server::Server},
+    };
+
+    #[test]
+    fn it_deserializes_a_server() {
+        let key = ssh_key::PrivateKey::random(&mut rand::rng(), Key::Ed25519)
+            .expect("Unable to generate a key");
+        let path = std::env::temp_dir().join(format!("assh-de-{}", std::process::id()));
+        key.write_openssh_file(&path, Default::default())
+            .expect("Unable to write the key");
+
+        let server: Server = toml::from_str(&format!(
+            r#"
+                id = "SSH-2.0-server_1.0 configured"
+                keys = ["{}"]
+                signature_algorithms = "-rsa-sha2-256"
+
+                [timeouts]
+                idle = "5m"
+
+                [rekey_limit]
+                time = "1h"
+
+                [algorithms]
+                profile = "modern"
+                kexs = "^curve25519-sha256"
+                ciphers = "-aes*,+aes256-ctr"
+                compressions = "no"
+            "#,
+            path.display()
+        ))
+        .expect("Unable to deserialize the server");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(server.id.to_string(), "SSH-2.0-server_1.0 configured");
+        assert_eq!(server.keys, vec![key]);
+        assert!(!server.signature_algorithms.contains(&Key::Rsa {
+            hash: Some(ssh_key::HashAlg::Sha256)
+        }));
+        assert_eq!(server.timeouts.idle, Some(Duration::from_secs(300)));
+        assert_eq!(server.timeouts.auth, Some(Duration::from_secs(120)));
+        assert_eq!(server.rekey_limit.time, Some(Duration::from_secs(3600)));
+        assert_eq!(server.algorithms.kexs[0], Kex::Curve25519Sha256);
+        assert_eq!(
+            server.algorithms.ciphers,
+            vec![Cipher::ChaCha20Poly1305, Cipher::Aes256Ctr]
+        );
+        assert_eq!(server.algorithms.compressions, vec![Compress::None]);
+    }
+
+    #[test]
+    fn it_deserializes_a_client() {
+        let client: Client = toml::from_str(
+            r#"
+                host = "example.com"
+                port = 2222
+
+                [timeouts]
+                kex = "30s"
+
+                [algorithms]
+                keys = ["ssh-ed25519", "rsa-sha2-512"]
+            "#,
+        )
+        .expect("Unable to deserialize the client");
+
+        assert_eq!(client.host, "example.com");
+        assert_eq!(client.port, 2222);
+        assert_eq!(client.timeouts.kex, Some(Duration::from_secs(30)));
+        assert_eq!(client.timeouts.id, Some(Duration::from_secs(60)));
+        assert_eq!(
+            client.algorithms.keys,
+            vec![
+                Key::Ed25519,
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha512)
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_unknown_algorithms() {
+        let err = toml::from_str::<Client>(
+            r#"
+                [algorithms]
+                ciphers = "+aes512-ctr"
+            "#,
+        )
+        .expect_err("The unknown cipher was accepted");
+
+        assert!(err.to_string().contains("Unknown algorithm `+aes512-ctr`"));
+    }
+}
@@ -6,9 +6,10 @@ use ssh_packet::{
     trans::{KexInit, NewKeys},
 };
 
+use super::{Negociated, extensions::ExtInfo};
 use crate::{
     Pipe, Result,
-    stream::{Stream, Transport},
+    stream::{RekeyLimit, Stream, Timeouts, Transport, algorithm::kex},
 };
 
 pub mod client;
@@ -17,6 +18,9 @@ use client::Client;
 pub mod server;
 use server::Server;
 
+#[cfg(feature = "serde")]
+mod de;
+
 mod private {
     pub trait Sealed {}
 
@@ -29,9 +33,25 @@ pub trait Side: private::Sealed + Send + Sync + Unpin + 'static {
     /// Get the [`Id`] for this session.
     fn id(&self) -> &Id;
 
+    /// Get the [`RekeyLimit`] for this session.
+    fn rekey_limit(&self) -> &RekeyLimit;
+
+    /// Get the [`Timeouts`] for this session.
+    fn timeouts(&self) -> &Timeouts;
+
+    /// Get the public host keys to announce to the peer once the connection service starts.
+    fn announced_keys(&self) -> impl Iterator<Item = &ssh_key::PublicKey>;
+
     /// Generate a [`KexInit`] message from the config.
     fn kexinit(&self) -> KexInit<'static>;
 
+    /// Generate the [`ExtInfo`] message to be sent after the first key-exchange,
+    /// if the peer advertised the support of extension negotiation in it's [`KexInit`].
+    fn extinfo(&self, peerkexinit: &KexInit) -> Option<ExtInfo>;
+
+    /// Whether the peer advertised the support of strict key-exchange in it's [`KexInit`].
+    fn is_strict(&self, peerkexinit: &KexInit) -> bool;
+
     /// Exchange the keys from the config.
     fn exchange(
         &self,
@@ -39,40 +59,49 @@ pub trait Side: private::Sealed + Send + Sync + Unpin + 'static {
         kexinit: &KexInit,
         peerkexinit: &KexInit,
         peer_id: &Id,
-    ) -> impl Future<Output = Result<Transport>> + Send + Sync;
+    ) -> impl Future<Output = Result<(Transport, Negociated)>> + Send + Sync;
 
-    /// Perform the key-exchange from this side.
+    /// Perform the key-exchange from this side,
+    /// once both sides have sent their [`KexInit`] message.
     fn kex(
         &self,
         stream: &mut Stream<impl Pipe>,
         peer_id: &Id,
-    ) -> impl Future<Output = Result<()>> + Send + Sync {
+        kexinit: &KexInit,
+        peerkexinit: &KexInit,
+    ) -> impl Future<Output = Result<Negociated>> + Send + Sync {
         async move {
-            tracing::debug!("Starting key-exchange procedure");
+            let first = stream.session_id().is_none();
+
+            if first && self.is_strict(peerkexinit) {
+                tracing::debug!("Enabling strict key-exchange with the peer");
 
-            let kexinit = self.kexinit();
-            stream.send(&kexinit).await?;
+                stream.set_strict()?;
+            }
 
-            // TODO: (compliance) Take care of `KexInit::first_kex_packet_follows` being true.
+            stream.set_kexing();
 
-            let peerkexinit = stream.recv().await?.to::<KexInit>()?;
+            if *peerkexinit.first_kex_packet_follows && !kex::guessed(kexinit, peerkexinit) {
+                tracing::debug!("Discarding the peer's wrongly guessed key-exchange packet");
 
-            let transport = self
-                .exchange(stream, &kexinit, &peerkexinit, peer_id)
-                .await?;
+                stream.recv().await?;
+            }
+
+            let (transport, negociated) =
+                self.exchange(stream, kexinit, peerkexinit, peer_id).await?;
 
             stream.send(&NewKeys).await?;
             stream.recv().await?.to::<NewKeys>()?;
 
-            tracing::debug!(
-                "Key exchange success, negociated algorithms:\nrx: {:?}\ntx: {:?}",
-                transport.rx,
-                transport.tx,
-            );
+            tracing::debug!("Key exchange success, negociated algorithms: {negociated:?}");
 
             stream.set_transport(transport);
 
-            Ok(())
+            if first && let Some(extinfo) = self.extinfo(peerkexinit) {
+                stream.send(&extinfo).await?;
+            }
+
+            Ok(negociated)
         }
     }
 }
@@ -1,153 +0,0 @@
-//! Server-[`Side`] implementation of the _session_.
-
-use rand::Rng;
-use ssh_key::Algorithm;
-use ssh_packet::{arch::NameList, trans::KexInit};
-
-use super::{Side, client::Client};
-use crate::{
-    Pipe, Result,
-    stream::{
-        Stream, Transport,
-        algorithm::{
-            Negociate,
-            cipher::Cipher,
-            compress::Compress,
-            hmac::Hmac,
-            kex::{Kex, KexMeta},
-        },
-    },
-};
-
-#[doc(no_inline)]
-pub use ssh_key::PrivateKey;
-#[doc(no_inline)]
-pub use ssh_packet::arch::id::Id;
-
-/// A _server_-side session configuration.
-#[derive(Debug, Clone)]
-pub struct Server {
-    /// [`Id`] for this _server_ session.
-    pub id: Id,
-
-    /// Server keys for key-exchange signature.
-    pub keys: Vec<PrivateKey>,
-
-    /// The algorithms enabled for this _server_ session.
-    pub algorithms: Algorithms,
-}
-
-impl Default for Server {
-    fn default() -> Self {
-        Self {
-            id: Id::v2(
-                concat!(
-                    env!("CARGO_PKG_NAME"),
-                    "@server:",
-                    env!("CARGO_PKG_VERSION")
-                ),
-                None::<&str>,
-            ),
-            keys: Default::default(),
-            algorithms: Default::default(),
-        }
-    }
-}
-
-/// Algorithms for a _server_-side session.
-#[derive(Debug, Clone)]
-pub struct Algorithms {
-    /// Enabled algorithms for _key-exchange_.
-    pub kexs: Vec<Kex>,
-
-    /// Enabled algorithms for _encryption & decryption_.
-    pub ciphers: Vec<Cipher>,
-
-    /// Enabled algorithms for _hmac_.
-    pub macs: Vec<Hmac>,
-
-    /// Enabled algorithms for _compression_.
-    pub compressions: Vec<Compress>,
-}
-
-impl Default for Algorithms {
-    fn default() -> Self {
-        Self {
-            kexs: vec![Kex::Curve25519Sha256, Kex::Curve25519Sha256Libssh],
-            ciphers: vec![
-                Cipher::Aes256Ctr,
-                Cipher::Aes192Ctr,
-                Cipher::Aes128Ctr,
-                Cipher::Aes256Cbc,
-                Cipher::Aes192Cbc,
-                Cipher::Aes128Cbc,
-                Cipher::TDesCbc,
-            ],
-            macs: vec![
-                Hmac::HmacSha512ETM,
-                Hmac::HmacSha256ETM,
-                Hmac::HmacSha512,
-                Hmac::HmacSha256,
-                Hmac::HmacSha1ETM,
-                Hmac::HmacSha1,
-                Hmac::HmacMd5ETM,
-                Hmac::HmacMd5,
-            ],
-            compressions: vec![Compress::ZlibOpenssh, Compress::Zlib, Compress::None],
-        }
-    }
-}
-
-impl Side for Server {
-    fn id(&self) -> &Id {
-        &self.id
-    }
-
-    fn kexinit(&self) -> KexInit<'static> {
-        let mut cookie = [0u8; 16];
-        rand::rng().fill_bytes(&mut cookie);
-
-        KexInit {
-            cookie,
-            kex_algorithms: NameList::from_iter(&self.algorithms.kexs),
-            server_host_key_algorithms: NameList::from_iter(
-                self.keys.iter().map(PrivateKey::algorithm),
-            ),
-            encryption_algorithms_client_to_server: NameList::from_iter(&self.algorithms.ciphers),
-            encryption_algorithms_server_to_client: NameList::from_iter(&self.algorithms.ciphers),
-            mac_algorithms_client_to_server: NameList::from_iter(&self.algorithms.macs),
-            mac_algorithms_server_to_client: NameList::from_iter(&self.algorithms.macs),
-            compression_algorithms_client_to_server: NameList::from_iter(
-                &self.algorithms.compressions,
-            ),
-            compression_algorithms_server_to_client: NameList::from_iter(
-                &self.algorithms.compressions,
-            ),
-            languages_client_to_server: NameList::default(),
-            languages_server_to_client: NameList::default(),
-            first_kex_packet_follows: false.into(),
-        }
-    }
-
-    async fn exchange(
-        &self,
-        stream: &mut Stream<impl Pipe>,
-        kexinit: &KexInit<'_>,
-        peerkexinit: &KexInit<'_>,
-        peer_id: &Id,
-    ) -> Result<Transport> {
-        let client = KexMeta::new::<Client>(peer_id, peerkexinit, kexinit)?;
-        let server = KexMeta::new::<Server>(self.id(), peerkexinit, kexinit)?;
-
-        let alg = Algorithm::negociate(peerkexinit, kexinit)?;
-        let key = self
-            .keys
-            .iter()
-            .find(|key| key.algorithm() == alg)
-            .expect("negociated server-key wasn't found");
-
-        Kex::negociate(peerkexinit, kexinit)?
-            .as_server(stream, client, server, key)
-            .await
-    }
-}
@@ -0,0 +1,88 @@
+//! Announcement and proof of the _server host keys_, for their rotation with the
+//! `hostkeys-00@openssh.com` and `hostkeys-prove-00@openssh.com` global requests.
+
+use ssh_key::{PublicKey, Signature};
+use ssh_packet::{
+    arch,
+    binrw::{self, BinWrite, binwrite},
+};
+
+use super::{HostKeySigner, Server};
+use crate::{
+    Result,
+    stream::algorithm::key::{self, Key},
+};
+
+/// The data signed to prove the possession of a host key in response to the
+/// `hostkeys-prove-00@openssh.com` global request, computed from the concatenation of the following.
+///
+/// see <https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL?annotate=HEAD>.
+#[binwrite]
+#[derive(Debug)]
+#[bw(big)]
+struct Proof<'b> {
+    #[bw(calc = arch::ascii!("hostkeys-prove-00@openssh.com"))]
+    kind: arch::Ascii<'b>,
+
+    /// The session identifier issued by the key-exchange.
+    session_id: arch::Bytes<'b>,
+
+    /// Host key blob.
+    blob: arch::Bytes<'b>,
+}
+
+impl Server {
+    /// The public host keys of the _server_, as announced to the _client_
+    /// in the `hostkeys-00@openssh.com` global request.
+    pub fn public_keys(&self) -> impl Iterator<Item = &PublicKey> {
+        self.signers().map(HostKeySigner::public_key)
+    }
+
+    /// Prove the possession of the host keys requested by the _client_ as `blobs` in the
+    /// `hostkeys-prove-00@openssh.com` global request, with a signature of each bound to the `session_id`.
+    ///
+    /// RSA keys are signed with the negociated _server host key_ `algorithm` when it's an RSA one,
+    /// and with `rsa-sha2-512` otherwise; this resolves to `None` if one of the keys isn't a host key.
+    pub async fn prove(
+        &self,
+        session_id: &[u8],
+        algorithm: &Key,
+        blobs: &[impl AsRef<[u8]>],
+    ) -> Result<Option<Vec<Signature>>> {
+        let mut signatures = Vec::with_capacity(blobs.len());
+
+        for blob in blobs {
+            let blob = blob.as_ref();
+
+            let Some(signer) = self.signers().find(|signer| {
+                signer
+                    .public_key()
+                    .to_bytes()
+                    .is_ok_and(|known| known == blob)
+            }) else {
+                tracing::debug!("Unable to prove the possession of an unknown host key");
+
+                return Ok(None);
+            };
+
+            let variants = key::variants(signer.public_key().algorithm());
+            let algorithm = if variants.contains(algorithm) {
+                algorithm
+            } else {
+                &variants[0]
+            };
+
+            let mut data = Vec::new();
+            Proof {
+                session_id: session_id.into(),
+                blob: blob.into(),
+            }
+            .write(&mut std::io::Cursor::new(&mut data))
+            .expect("The binrw structure serialization failed");
+
+            signatures.push(signer.sign(algorithm, &data).await?);
+        }
+
+        Ok(Some(signatures))
+    }
+}
@@ -0,0 +1,475 @@
+//! Server-[`Side`] implementation of the _session_.
+
+use std::{sync::Arc, time::Duration};
+
+use rand::Rng;
+use ssh_key::{Algorithm, Certificate, EcdsaCurve, HashAlg};
+use ssh_packet::{
+    arch::{Ascii, NameList},
+    trans::KexInit,
+};
+
+use super::{Side, client::Client};
+use crate::{
+    Error, Pipe, Result,
+    error::ListError,
+    session::{
+        Negociated,
+        extensions::{self, ExtInfo, Extension},
+    },
+    stream::{
+        Stream, Transport,
+        algorithm::{
+            Negociate,
+            cipher::Cipher,
+            compress::Compress,
+            hmac::Hmac,
+            kex::{self, Kex, KexMeta},
+            key::{self, HostKey},
+            list,
+        },
+    },
+};
+
+mod signer;
+pub use signer::{HostKeySigner, Signing};
+
+mod hostkeys;
+
+pub use crate::stream::algorithm::kex::moduli::{Moduli, ModuliFile, Modulus, SystemModuli};
+pub use crate::stream::{RekeyLimit, Timeouts};
+
+#[doc(no_inline)]
+pub use ssh_key::PrivateKey;
+#[doc(no_inline)]
+pub use ssh_packet::arch::id::Id;
+
+/// A _server_-side session configuration.
+///
+/// With the `serde` feature, the configuration can be deserialized, with the [`Id`] in it's
+/// textual form, the keys, certificates and moduli as paths to their files, and the algorithms
+/// in the OpenSSH list syntax of [`algorithm::alter`](crate::algorithm::alter).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Server {
+    /// [`Id`] for this _server_ session.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "super::de::id"))]
+    pub id: Id,
+
+    /// Server keys for key-exchange signature.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "super::de::keys"))]
+    pub keys: Vec<PrivateKey>,
+
+    /// Server host key signers for key-exchange signature, which private keys
+    /// are held outside of the process, offered after the `keys`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub signers: Vec<Arc<dyn HostKeySigner>>,
+
+    /// OpenSSH certificates of the `keys`, presented to the _client_ in place of their
+    /// public keys when it supports the certificate algorithms, like OpenSSH's `HostCertificate`.
+    ///
+    /// Each certificate is paired with the key or signer it certifies, and ignored if there is none.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "super::de::certificates"))]
+    pub certificates: Vec<Certificate>,
+
+    /// Signature algorithms accepted for _public key_ authentication,
+    /// advertised to the _client_ in the `server-sig-algs` extension.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "super::de::signature_algorithms")
+    )]
+    pub signature_algorithms: Vec<Algorithm>,
+
+    /// Provider of groups for the _Diffie-Hellman group-exchange_ key-exchanges,
+    /// defaulting to the system's moduli, see [`SystemModuli`].
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "super::de::moduli"))]
+    pub moduli: Arc<dyn Moduli>,
+
+    /// Limits of usage of the session keys before a re-exchange.
+    pub rekey_limit: RekeyLimit,
+
+    /// Deadlines of the session before disconnecting the _client_,
+    /// with a default authentication grace period of 120 seconds.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "super::de::server_timeouts")
+    )]
+    pub timeouts: Timeouts,
+
+    /// The algorithms enabled for this _server_ session.
+    pub algorithms: Algorithms,
+
+    /// Whether the host keys are announced to the _client_ with the `hostkeys-00@openssh.com`
+    /// _global request_ once the connection service starts, for it to learn the rotated keys.
+    pub announce_host_keys: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            id: Id::v2(
+                concat!(
+                    env!("CARGO_PKG_NAME"),
+                    "@server:",
+                    env!("CARGO_PKG_VERSION")
+                ),
+                None::<&str>,
+            ),
+            keys: Default::default(),
+            signers: Default::default(),
+            certificates: Default::default(),
+            signature_algorithms: Self::default_signature_algorithms(),
+            moduli: Arc::new(SystemModuli::default()),
+            rekey_limit: Default::default(),
+            timeouts: Self::default_timeouts(),
+            algorithms: Default::default(),
+            announce_host_keys: true,
+        }
+    }
+}
+
+impl Server {
+    pub(crate) fn default_signature_algorithms() -> Vec<Algorithm> {
+        vec![
+            Algorithm::Ed25519,
+            Algorithm::SkEd25519,
+            Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP256,
+            },
+            Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP384,
+            },
+            Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP521,
+            },
+            Algorithm::SkEcdsaSha2NistP256,
+            Algorithm::Rsa {
+                hash: Some(HashAlg::Sha512),
+            },
+            Algorithm::Rsa {
+                hash: Some(HashAlg::Sha256),
+            },
+            #[cfg(feature = "insecure")]
+            Algorithm::Rsa { hash: None },
+        ]
+    }
+
+    pub(crate) fn default_timeouts() -> Timeouts {
+        Timeouts {
+            auth: Some(Duration::from_secs(120)),
+            ..Default::default()
+        }
+    }
+
+    /// The host key signers, with the `keys` first and then the `signers`.
+    fn signers(&self) -> impl Iterator<Item = &dyn HostKeySigner> + Clone {
+        self.keys
+            .iter()
+            .map(|key| key as &dyn HostKeySigner)
+            .chain(self.signers.iter().map(AsRef::as_ref))
+    }
+
+    /// The host keys in order of preference, with the certified ones first,
+    /// each once per _server host key_ algorithm it's usable with.
+    fn host_keys(&self) -> impl Iterator<Item = HostKey<'_>> {
+        let certified = self.certificates.iter().filter_map(|certificate| {
+            self.signers()
+                .find(|key| key.public_key().key_data() == certificate.public_key())
+                .map(|key| (key, Some(certificate)))
+        });
+        let plain = self.signers().map(|key| (key, None));
+
+        certified.chain(plain).flat_map(|(key, certificate)| {
+            key::variants(key.public_key().algorithm())
+                .into_iter()
+                .map(move |algorithm| HostKey {
+                    key,
+                    algorithm,
+                    certificate,
+                })
+        })
+    }
+}
+
+/// Algorithms for a _server_-side session.
+#[derive(Debug, Clone)]
+pub struct Algorithms {
+    /// Enabled algorithms for _key-exchange_.
+    pub kexs: Vec<Kex>,
+
+    /// Enabled algorithms for _encryption & decryption_.
+    pub ciphers: Vec<Cipher>,
+
+    /// Enabled algorithms for _hmac_.
+    pub macs: Vec<Hmac>,
+
+    /// Enabled algorithms for _compression_.
+    pub compressions: Vec<Compress>,
+}
+
+impl Default for Algorithms {
+    fn default() -> Self {
+        Self::compatible()
+    }
+}
+
+impl Algorithms {
+    /// Only the state-of-the-art algorithms, with post-quantum hybrid and curve25519
+    /// _key-exchanges_ and AEAD ciphers, suitable when all the peers are recent.
+    pub fn modern() -> Self {
+        Self {
+            kexs: vec![
+                Kex::Mlkem768X25519Sha256,
+                Kex::Sntrup761X25519Sha512,
+                Kex::Sntrup761X25519Sha512Openssh,
+                Kex::Curve25519Sha256,
+                Kex::Curve25519Sha256Libssh,
+            ],
+            ciphers: vec![
+                Cipher::ChaCha20Poly1305,
+                Cipher::Aes256Gcm,
+                Cipher::Aes128Gcm,
+            ],
+            macs: vec![Hmac::Umac128ETM, Hmac::HmacSha512ETM, Hmac::HmacSha256ETM],
+            compressions: vec![Compress::ZlibOpenssh, Compress::None],
+        }
+    }
+
+    /// The secure algorithms widely supported by the peers, which is the default.
+    pub fn compatible() -> Self {
+        Self {
+            kexs: vec![
+                Kex::Mlkem768X25519Sha256,
+                Kex::Sntrup761X25519Sha512,
+                Kex::Sntrup761X25519Sha512Openssh,
+                Kex::Curve25519Sha256,
+                Kex::Curve25519Sha256Libssh,
+                Kex::EcdhSha2Nistp256,
+                Kex::EcdhSha2Nistp384,
+                Kex::EcdhSha2Nistp521,
+                Kex::DiffieHellmanGroupExchangeSha256,
+                Kex::DiffieHellmanGroup16Sha512,
+                Kex::DiffieHellmanGroup18Sha512,
+                Kex::DiffieHellmanGroup14Sha256,
+            ],
+            ciphers: vec![
+                Cipher::ChaCha20Poly1305,
+                Cipher::Aes256Gcm,
+                Cipher::Aes128Gcm,
+                Cipher::Aes256Ctr,
+                Cipher::Aes192Ctr,
+                Cipher::Aes128Ctr,
+            ],
+            macs: vec![
+                Hmac::Umac128ETM,
+                Hmac::Umac64ETM,
+                Hmac::HmacSha512ETM,
+                Hmac::HmacSha256ETM,
+                Hmac::HmacSha512,
+                Hmac::HmacSha256,
+                Hmac::Umac128,
+                Hmac::Umac64,
+            ],
+            compressions: vec![Compress::ZlibOpenssh, Compress::Zlib, Compress::None],
+        }
+    }
+
+    /// Only the algorithms approved by FIPS 140-3, with NIST curves or MODP groups
+    /// for the _key-exchanges_, AES ciphers and sha-2 HMACs.
+    ///
+    /// This restricts the negociated algorithms, but does not make the implementations certified.
+    pub fn fips() -> Self {
+        Self {
+            kexs: vec![
+                Kex::EcdhSha2Nistp256,
+                Kex::EcdhSha2Nistp384,
+                Kex::EcdhSha2Nistp521,
+                Kex::DiffieHellmanGroupExchangeSha256,
+                Kex::DiffieHellmanGroup16Sha512,
+                Kex::DiffieHellmanGroup18Sha512,
+                Kex::DiffieHellmanGroup14Sha256,
+            ],
+            ciphers: vec![
+                Cipher::Aes256Gcm,
+                Cipher::Aes128Gcm,
+                Cipher::Aes256Ctr,
+                Cipher::Aes192Ctr,
+                Cipher::Aes128Ctr,
+            ],
+            macs: vec![
+                Hmac::HmacSha512ETM,
+                Hmac::HmacSha256ETM,
+                Hmac::HmacSha512,
+                Hmac::HmacSha256,
+            ],
+            compressions: vec![Compress::ZlibOpenssh, Compress::Zlib, Compress::None],
+        }
+    }
+
+    /// Alter the algorithms with an OpenSSH configuration option, such as `Ciphers -aes*-ctr`,
+    /// with the list syntax of [`algorithm::alter`](crate::algorithm::alter).
+    ///
+    /// The supported options are `KexAlgorithms`, `Ciphers`, `MACs` and `Compression`,
+    /// which also accepts `yes`, `delayed` and `no`.
+    pub fn apply(&mut self, option: &str) -> Result<(), ListError> {
+        let (keyword, list) = list::option(option)?;
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "kexalgorithms" => self.kexs = list::alter(&self.kexs, list)?,
+            "ciphers" => self.ciphers = list::alter(&self.ciphers, list)?,
+            "macs" => self.macs = list::alter(&self.macs, list)?,
+            "compression" => self.compressions = list::compressions(&self.compressions, list)?,
+            _ => return Err(ListError::Option(keyword.into())),
+        }
+
+        Ok(())
+    }
+
+    /// The [`Self::compatible`] algorithms, followed by the weak ones
+    /// to interoperate with legacy peers.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+    pub fn legacy() -> Self {
+        let mut algorithms = Self::compatible();
+
+        algorithms.kexs.extend([
+            Kex::DiffieHellmanGroupExchangeSha1,
+            Kex::DiffieHellmanGroup14Sha1,
+            Kex::DiffieHellmanGroup1Sha1,
+        ]);
+        algorithms.ciphers.extend([
+            Cipher::Aes256Cbc,
+            Cipher::Aes192Cbc,
+            Cipher::Aes128Cbc,
+            Cipher::TDesCbc,
+        ]);
+        algorithms.macs.extend([
+            Hmac::HmacSha1ETM,
+            Hmac::HmacSha1,
+            Hmac::HmacMd5ETM,
+            Hmac::HmacMd5,
+        ]);
+
+        algorithms
+    }
+}
+
+impl Side for Server {
+    fn id(&self) -> &Id {
+        &self.id
+    }
+
+    fn rekey_limit(&self) -> &RekeyLimit {
+        &self.rekey_limit
+    }
+
+    fn timeouts(&self) -> &Timeouts {
+        &self.timeouts
+    }
+
+    fn announced_keys(&self) -> impl Iterator<Item = &ssh_key::PublicKey> {
+        self.announce_host_keys
+            .then(|| self.public_keys())
+            .into_iter()
+            .flatten()
+    }
+
+    fn kexinit(&self) -> KexInit<'static> {
+        let mut cookie = [0u8; 16];
+        rand::rng().fill_bytes(&mut cookie);
+
+        KexInit {
+            cookie,
+            kex_algorithms: NameList::from_iter(
+                self.algorithms
+                    .kexs
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .chain([kex::EXT_INFO_S, kex::KEX_STRICT_S]),
+            ),
+            server_host_key_algorithms: NameList::from_iter(self.host_keys().map(|key| key.name())),
+            encryption_algorithms_client_to_server: NameList::from_iter(&self.algorithms.ciphers),
+            encryption_algorithms_server_to_client: NameList::from_iter(&self.algorithms.ciphers),
+            mac_algorithms_client_to_server: NameList::from_iter(&self.algorithms.macs),
+            mac_algorithms_server_to_client: NameList::from_iter(&self.algorithms.macs),
+            compression_algorithms_client_to_server: NameList::from_iter(
+                &self.algorithms.compressions,
+            ),
+            compression_algorithms_server_to_client: NameList::from_iter(
+                &self.algorithms.compressions,
+            ),
+            languages_client_to_server: NameList::default(),
+            languages_server_to_client: NameList::default(),
+            first_kex_packet_follows: false.into(),
+        }
+    }
+
+    fn extinfo(&self, peerkexinit: &KexInit) -> Option<ExtInfo> {
+        peerkexinit
+            .kex_algorithms
+            .into_iter()
+            .any(|name| &*name == kex::EXT_INFO_C)
+            .then(|| ExtInfo {
+                extensions: vec![
+                    Extension {
+                        name: Ascii::borrowed(extensions::SERVER_SIG_ALGS)
+                            .expect("the name is ASCII"),
+                        value: NameList::from_iter(&self.signature_algorithms)
+                            .0
+                            .as_bytes()
+                            .to_vec()
+                            .into(),
+                    },
+                    Extension {
+                        name: Ascii::borrowed(extensions::PING).expect("the name is ASCII"),
+                        value: b"0".to_vec().into(),
+                    },
+                ],
+            })
+    }
+
+    fn is_strict(&self, peerkexinit: &KexInit) -> bool {
+        peerkexinit
+            .kex_algorithms
+            .into_iter()
+            .any(|name| &*name == kex::KEX_STRICT_C)
+    }
+
+    async fn exchange(
+        &self,
+        stream: &mut Stream<impl Pipe>,
+        kexinit: &KexInit<'_>,
+        peerkexinit: &KexInit<'_>,
+        peer_id: &Id,
+    ) -> Result<(Transport, Negociated)> {
+        let client = KexMeta::new::<Client>(peer_id, peerkexinit, kexinit)?;
+        let server = KexMeta::new::<Server>(self.id(), peerkexinit, kexinit)?;
+        let (client_to_server, server_to_client) = ((&client).into(), (&server).into());
+
+        let alg = peerkexinit
+            .server_host_key_algorithms
+            .preferred_in(&kexinit.server_host_key_algorithms)
+            .ok_or(Error::NoCommonKey)?;
+        let key = self
+            .host_keys()
+            .find(|key| *alg == key.name())
+            .expect("negociated server-key wasn't found");
+
+        let kex = Kex::negociate(peerkexinit, kexinit)?;
+        let transport = kex
+            .as_server(stream, client, server, key.clone(), self.moduli.as_ref())
+            .await?;
+
+        let negociated = Negociated {
+            kex,
+            key: key.algorithm.clone(),
+            host_key: key.key.public_key().clone(),
+            certificate: key.certificate.cloned(),
+            client_to_server,
+            server_to_client,
+        };
+
+        Ok((transport, negociated))
+    }
+}
@@ -0,0 +1,41 @@
+//! Signing of the exchange hash with the _server host keys_.
+
+use std::pin::Pin;
+
+use futures::Future;
+use ssh_key::{Algorithm, PrivateKey, PublicKey, Signature, private::KeypairData};
+
+/// The future returned by [`HostKeySigner::sign`].
+pub type Signing<'a> =
+    Pin<Box<dyn Future<Output = signature::Result<Signature>> + Send + Sync + 'a>>;
+
+/// A signer with a _server host key_, which private part may be held outside of the process,
+/// such as in an `ssh-agent`, an HSM or a signing daemon.
+pub trait HostKeySigner: std::fmt::Debug + Send + Sync {
+    /// The public part of the host key, presented to the _client_.
+    fn public_key(&self) -> &PublicKey;
+
+    /// Sign the `data` with the host key for the negociated _server host key_ `algorithm`,
+    /// resolving to the signature or an error to abort the key-exchange.
+    ///
+    /// The `algorithm` only differs from the key's for RSA keys, where it selects the hash
+    /// of the signature, either `rsa-sha2-512` or `rsa-sha2-256`.
+    fn sign<'a>(&'a self, algorithm: &'a Algorithm, data: &'a [u8]) -> Signing<'a>;
+}
+
+impl HostKeySigner for PrivateKey {
+    fn public_key(&self) -> &PublicKey {
+        self.public_key()
+    }
+
+    fn sign<'a>(&'a self, algorithm: &'a Algorithm, data: &'a [u8]) -> Signing<'a> {
+        let signature = match (algorithm, self.key_data()) {
+            (Algorithm::Rsa { hash }, KeypairData::Rsa(keypair)) => {
+                signature::Signer::try_sign(&(keypair, *hash), data)
+            }
+            _ => signature::Signer::try_sign(self, data),
+        };
+
+        Box::pin(async move { signature })
+    }
+}
@@ -1,7 +1,8 @@
-use cipher::KeyIvInit;
-use digest::{Digest, FixedOutputReset};
+use aead::{AeadCore, AeadInOut, KeyInit, consts::U12};
+use cipher::{KeyIvInit, StreamCipher};
+use digest::{Digest, FixedOutputReset, MacError};
 use ssh_packet::{arch::NameList, trans::KexInit};
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, EnumString};
 
 use crate::{
     Error, Result,
@@ -24,25 +25,23 @@ impl super::Negociate<Server> for Cipher {
     }
 }
 
-// TODO: (feature) Implement the latest and safest ciphers (`chacha20-poly1305@openssh.com`, `aes256-gcm@openssh.com`, `aes128-gcm@openssh.com`).
-
 /// SSH cipher algorithms.
 #[non_exhaustive]
-#[derive(Debug, Clone, EnumString, AsRefStr)]
+#[derive(Debug, Clone, PartialEq, EnumString, AsRefStr, EnumIter)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Cipher {
-    // /// ChaCha20-Poly1305.
-    // #[strum(serialize = "chacha20-poly1305@openssh.com")]
-    // ChaCha20Poly1305,
-
-    // /// AES-256 in Galois/Counter Mode (GCM).
-    // #[strum(serialize = "aes256-gcm@openssh.com")]
-    // Aes256Gcm,
-
-    // /// AES-128 in Galois/Counter Mode (GCM).
-    // #[strum(serialize = "aes128-gcm@openssh.com")]
-    // Aes128Gcm,
-    //
+    /// ChaCha20-Poly1305.
+    #[strum(serialize = "chacha20-poly1305@openssh.com")]
+    ChaCha20Poly1305,
+
+    /// AES-256 in Galois/Counter Mode (GCM).
+    #[strum(serialize = "aes256-gcm@openssh.com")]
+    Aes256Gcm,
+
+    /// AES-128 in Galois/Counter Mode (GCM).
+    #[strum(serialize = "aes128-gcm@openssh.com")]
+    Aes128Gcm,
+
     /// AES-256 in counter (CTR) mode.
     Aes256Ctr,
 
@@ -53,22 +52,169 @@ pub enum Cipher {
     Aes128Ctr,
 
     /// AES-256 in cipher block chaining (CBC) mode.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     Aes256Cbc,
 
     /// AES-192 in cipher block chaining (CBC) mode.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     Aes192Cbc,
 
     /// AES-128 in cipher block chaining (CBC) mode.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     Aes128Cbc,
 
     /// TripleDES in cipher block chaining (CBC) mode.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     #[strum(serialize = "3des-cbc")]
     TDesCbc,
 
     /// No cipher algorithm.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     None,
 }
 
+impl Cipher {
+    /// Whether the cipher is an AEAD, authenticating the packets in place of the HMAC.
+    pub fn authenticated(&self) -> bool {
+        matches!(
+            self,
+            Self::ChaCha20Poly1305 | Self::Aes256Gcm | Self::Aes128Gcm
+        )
+    }
+}
+
+/// The size of the authentication tag of the AEAD ciphers.
+pub const TAG_SIZE: usize = 16;
+
+/// The authentication tag of the AEAD ciphers.
+pub type Tag = [u8; TAG_SIZE];
+
+/// The OpenSSH `chacha20-poly1305@openssh.com` construction, with a _header_ key to encrypt
+/// the packet length and a _main_ key to encrypt and authenticate the packet.
+///
+/// see <https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL.chacha20poly1305?rev=HEAD>.
+pub struct ChaCha20Poly1305 {
+    header: ssh_cipher::ChaChaKey,
+    main: ssh_cipher::ChaCha20Poly1305,
+}
+
+impl ChaCha20Poly1305 {
+    fn new(key: [u8; 64]) -> Self {
+        let (main, header) = key.split_at(32);
+
+        Self {
+            header: ssh_cipher::ChaChaKey::try_from(header).expect("the key is 32 bytes long"),
+            main: ssh_cipher::ChaCha20Poly1305::new_from_slice(main)
+                .expect("the key is 32 bytes long"),
+        }
+    }
+
+    fn nonce(seq: u32) -> ssh_cipher::ChaChaNonce {
+        (seq as u64).to_be_bytes().into()
+    }
+
+    fn length(&self, seq: u32, length: &mut [u8]) {
+        ssh_cipher::ChaCha20::new(&self.header, &Self::nonce(seq)).apply_keystream(length);
+    }
+
+    fn seal(&self, seq: u32, buffer: &mut [u8]) -> Result<Tag> {
+        let (length, data) = buffer.split_at_mut(4);
+        self.length(seq, length);
+
+        Ok(self
+            .main
+            .encrypt_inout_detached(&Self::nonce(seq), length, data.into())
+            .map_err(|_| Error::Cipher)?
+            .into())
+    }
+
+    fn open(&self, seq: u32, buffer: &mut [u8], tag: &[u8]) -> Result<()> {
+        let (length, data) = buffer.split_at_mut(4);
+
+        self.main
+            .decrypt_inout_detached(
+                &Self::nonce(seq),
+                length,
+                data.into(),
+                tag.try_into().map_err(|_| MacError)?,
+            )
+            .map_err(|_| MacError)?;
+        self.length(seq, length);
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ChaCha20Poly1305 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaCha20Poly1305").finish_non_exhaustive()
+    }
+}
+
+/// The AES-GCM construction, with the packet length as associated data
+/// and the invocation counter of the nonce incremented for each packet.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc5647#section-7>.
+pub struct Gcm<C> {
+    cipher: C,
+    nonce: [u8; 12],
+}
+
+impl<C: AeadInOut + AeadCore<NonceSize = U12> + KeyInit> Gcm<C> {
+    fn new(key: &[u8], nonce: [u8; 12]) -> Self {
+        Self {
+            cipher: C::new_from_slice(key).expect("the key is of the cipher's size"),
+            nonce,
+        }
+    }
+
+    fn increment(&mut self) {
+        let (_, counter) = self.nonce.split_at_mut(4);
+        let value = u64::from_be_bytes(counter.try_into().expect("the counter is 8 bytes long"));
+
+        counter.copy_from_slice(&value.wrapping_add(1).to_be_bytes());
+    }
+
+    fn seal(&mut self, buffer: &mut [u8]) -> Result<Tag> {
+        let (length, data) = buffer.split_at_mut(4);
+
+        let tag = self
+            .cipher
+            .encrypt_inout_detached(&self.nonce.into(), length, data.into())
+            .map_err(|_| Error::Cipher)?;
+        self.increment();
+
+        Ok(tag.as_slice().try_into().expect("the tag is 16 bytes long"))
+    }
+
+    fn open(&mut self, buffer: &mut [u8], tag: &[u8]) -> Result<()> {
+        let (length, data) = buffer.split_at_mut(4);
+
+        self.cipher
+            .decrypt_inout_detached(
+                &self.nonce.into(),
+                length,
+                data.into(),
+                tag.try_into().map_err(|_| MacError)?,
+            )
+            .map_err(|_| MacError)?;
+        self.increment();
+
+        Ok(())
+    }
+}
+
+impl<C> std::fmt::Debug for Gcm<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gcm").finish_non_exhaustive()
+    }
+}
+
 fn ctr<C: ctr::cipher::StreamCipher>(state: &mut C, buffer: &mut [u8]) -> Result<()> {
     state
         .try_apply_keystream(buffer)
@@ -79,12 +225,19 @@ fn ctr<C: ctr::cipher::StreamCipher>(state: &mut C, buffer: &mut [u8]) -> Result
 
 #[derive(Debug, Default)]
 pub enum EncState {
+    ChaCha20Poly1305(Box<ChaCha20Poly1305>),
+    Aes256Gcm(Box<Gcm<aes_gcm::Aes256Gcm>>),
+    Aes128Gcm(Box<Gcm<aes_gcm::Aes128Gcm>>),
     Aes256Ctr(ctr::Ctr128BE<aes::Aes256>),
     Aes192Ctr(ctr::Ctr128BE<aes::Aes192>),
     Aes128Ctr(ctr::Ctr128BE<aes::Aes128>),
+    #[cfg(feature = "insecure")]
     Aes256Cbc(cbc::Encryptor<aes::Aes256>),
+    #[cfg(feature = "insecure")]
     Aes192Cbc(cbc::Encryptor<aes::Aes192>),
+    #[cfg(feature = "insecure")]
     Aes128Cbc(cbc::Encryptor<aes::Aes128>),
+    #[cfg(feature = "insecure")]
     TDesCbc(cbc::Encryptor<des::TdesEde3>),
     #[default]
     None,
@@ -98,6 +251,17 @@ impl EncState {
         session_id: &[u8],
     ) -> Self {
         match cipher {
+            Cipher::ChaCha20Poly1305 => Self::ChaCha20Poly1305(Box::new(ChaCha20Poly1305::new(
+                super::kdf::<_, H>(K, secret, hash, session_id),
+            ))),
+            Cipher::Aes256Gcm => Self::Aes256Gcm(Box::new(Gcm::new(
+                &super::kdf::<32, H>(K, secret, hash, session_id),
+                super::kdf::<_, H>(IV, secret, hash, session_id),
+            ))),
+            Cipher::Aes128Gcm => Self::Aes128Gcm(Box::new(Gcm::new(
+                &super::kdf::<16, H>(K, secret, hash, session_id),
+                super::kdf::<_, H>(IV, secret, hash, session_id),
+            ))),
             Cipher::Aes256Ctr => Self::Aes256Ctr(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
@@ -110,26 +274,32 @@ impl EncState {
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes256Cbc => Self::Aes256Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes192Cbc => Self::Aes192Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes128Cbc => Self::Aes128Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::TDesCbc => Self::TDesCbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::None => Self::None,
         }
     }
 
+    #[cfg(feature = "insecure")]
     fn cbc<C: cbc::cipher::BlockModeEncrypt>(state: &mut C, buffer: &mut [u8]) -> Result<()> {
         use cbc::cipher::inout;
 
@@ -153,35 +323,70 @@ impl EncState {
             Self::Aes256Ctr(state) => ctr(state, buffer),
             Self::Aes192Ctr(state) => ctr(state, buffer),
             Self::Aes128Ctr(state) => ctr(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes256Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes192Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes128Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::TDesCbc(state) => Self::cbc(state, buffer),
+            Self::ChaCha20Poly1305(_) | Self::Aes256Gcm(_) | Self::Aes128Gcm(_) => {
+                Err(Error::Cipher)
+            }
             Self::None => Ok(()),
         }
     }
 
+    /// Whether the state is an AEAD, authenticating the packets in place of the HMAC.
+    pub fn authenticated(&self) -> bool {
+        matches!(
+            self,
+            Self::ChaCha20Poly1305 { .. } | Self::Aes256Gcm { .. } | Self::Aes128Gcm { .. }
+        )
+    }
+
+    /// Encrypt and authenticate the `buffer`, including it's length prefix, producing the tag.
+    pub fn seal(&mut self, seq: u32, buffer: &mut [u8]) -> Result<Tag> {
+        match self {
+            Self::ChaCha20Poly1305(state) => state.seal(seq, buffer),
+            Self::Aes256Gcm(state) => state.seal(buffer),
+            Self::Aes128Gcm(state) => state.seal(buffer),
+            _ => Err(Error::Cipher),
+        }
+    }
+
     pub fn block_size(&self) -> usize {
         match self {
-            Self::None | Self::TDesCbc { .. } => 8,
-            Self::Aes128Cbc { .. }
-            | Self::Aes192Cbc { .. }
-            | Self::Aes256Cbc { .. }
-            | Self::Aes128Ctr { .. }
+            Self::None | Self::ChaCha20Poly1305 { .. } => 8,
+            #[cfg(feature = "insecure")]
+            Self::TDesCbc { .. } => 8,
+            #[cfg(feature = "insecure")]
+            Self::Aes128Cbc { .. } | Self::Aes192Cbc { .. } | Self::Aes256Cbc { .. } => 16,
+            Self::Aes128Ctr { .. }
             | Self::Aes192Ctr { .. }
-            | Self::Aes256Ctr { .. } => 16,
+            | Self::Aes256Ctr { .. }
+            | Self::Aes128Gcm { .. }
+            | Self::Aes256Gcm { .. } => 16,
         }
     }
 }
 
 #[derive(Debug, Default)]
 pub enum DecState {
+    ChaCha20Poly1305(Box<ChaCha20Poly1305>),
+    Aes256Gcm(Box<Gcm<aes_gcm::Aes256Gcm>>),
+    Aes128Gcm(Box<Gcm<aes_gcm::Aes128Gcm>>),
     Aes256Ctr(ctr::Ctr128BE<aes::Aes256>),
     Aes192Ctr(ctr::Ctr128BE<aes::Aes192>),
     Aes128Ctr(ctr::Ctr128BE<aes::Aes128>),
+    #[cfg(feature = "insecure")]
     Aes256Cbc(cbc::Decryptor<aes::Aes256>),
+    #[cfg(feature = "insecure")]
     Aes192Cbc(cbc::Decryptor<aes::Aes192>),
+    #[cfg(feature = "insecure")]
     Aes128Cbc(cbc::Decryptor<aes::Aes128>),
+    #[cfg(feature = "insecure")]
     TDesCbc(cbc::Decryptor<des::TdesEde3>),
     #[default]
     None,
@@ -195,6 +400,17 @@ impl DecState {
         session_id: &[u8],
     ) -> Self {
         match cipher {
+            Cipher::ChaCha20Poly1305 => Self::ChaCha20Poly1305(Box::new(ChaCha20Poly1305::new(
+                super::kdf::<_, H>(K, secret, hash, session_id),
+            ))),
+            Cipher::Aes256Gcm => Self::Aes256Gcm(Box::new(Gcm::new(
+                &super::kdf::<32, H>(K, secret, hash, session_id),
+                super::kdf::<_, H>(IV, secret, hash, session_id),
+            ))),
+            Cipher::Aes128Gcm => Self::Aes128Gcm(Box::new(Gcm::new(
+                &super::kdf::<16, H>(K, secret, hash, session_id),
+                super::kdf::<_, H>(IV, secret, hash, session_id),
+            ))),
             Cipher::Aes256Ctr => Self::Aes256Ctr(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
@@ -207,26 +423,32 @@ impl DecState {
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes256Cbc => Self::Aes256Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes192Cbc => Self::Aes192Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::Aes128Cbc => Self::Aes128Cbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::TDesCbc => Self::TDesCbc(KeyIvInit::new(
                 &super::kdf::<_, H>(K, secret, hash, session_id).into(),
                 &super::kdf::<_, H>(IV, secret, hash, session_id).into(),
             )),
+            #[cfg(feature = "insecure")]
             Cipher::None => Self::None,
         }
     }
 
+    #[cfg(feature = "insecure")]
     fn cbc<C: cbc::cipher::BlockModeDecrypt>(cipher: &mut C, buffer: &mut [u8]) -> Result<()> {
         use cbc::cipher::inout;
 
@@ -250,23 +472,62 @@ impl DecState {
             Self::Aes256Ctr(state) => ctr(state, buffer),
             Self::Aes192Ctr(state) => ctr(state, buffer),
             Self::Aes128Ctr(state) => ctr(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes256Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes192Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::Aes128Cbc(state) => Self::cbc(state, buffer),
+            #[cfg(feature = "insecure")]
             Self::TDesCbc(state) => Self::cbc(state, buffer),
+            Self::ChaCha20Poly1305(_) | Self::Aes256Gcm(_) | Self::Aes128Gcm(_) => {
+                Err(Error::Cipher)
+            }
             Self::None => Ok(()),
         }
     }
 
+    /// Whether the state is an AEAD, authenticating the packets in place of the HMAC.
+    pub fn authenticated(&self) -> bool {
+        matches!(
+            self,
+            Self::ChaCha20Poly1305 { .. } | Self::Aes256Gcm { .. } | Self::Aes128Gcm { .. }
+        )
+    }
+
+    /// Decrypt the packet `length` prefix, without authenticating it.
+    pub fn length(&self, seq: u32, mut length: [u8; 4]) -> Result<u32> {
+        match self {
+            Self::ChaCha20Poly1305(state) => state.length(seq, &mut length),
+            Self::Aes256Gcm(_) | Self::Aes128Gcm(_) => (),
+            _ => return Err(Error::Cipher),
+        }
+
+        Ok(u32::from_be_bytes(length))
+    }
+
+    /// Authenticate and decrypt the `buffer`, including it's length prefix, with the provided `tag`.
+    pub fn open(&mut self, seq: u32, buffer: &mut [u8], tag: &[u8]) -> Result<()> {
+        match self {
+            Self::ChaCha20Poly1305(state) => state.open(seq, buffer, tag),
+            Self::Aes256Gcm(state) => state.open(buffer, tag),
+            Self::Aes128Gcm(state) => state.open(buffer, tag),
+            _ => Err(Error::Cipher),
+        }
+    }
+
     pub fn block_size(&self) -> usize {
         match self {
-            Self::None | Self::TDesCbc { .. } => 8,
-            Self::Aes128Cbc { .. }
-            | Self::Aes192Cbc { .. }
-            | Self::Aes256Cbc { .. }
-            | Self::Aes128Ctr { .. }
+            Self::None | Self::ChaCha20Poly1305 { .. } => 8,
+            #[cfg(feature = "insecure")]
+            Self::TDesCbc { .. } => 8,
+            #[cfg(feature = "insecure")]
+            Self::Aes128Cbc { .. } | Self::Aes192Cbc { .. } | Self::Aes256Cbc { .. } => 16,
+            Self::Aes128Ctr { .. }
             | Self::Aes192Ctr { .. }
-            | Self::Aes256Ctr { .. } => 16,
+            | Self::Aes256Ctr { .. }
+            | Self::Aes128Gcm { .. }
+            | Self::Aes256Gcm { .. } => 16,
         }
     }
 }
@@ -1,7 +1,6 @@
-use std::io::{Read, Write};
-
+use flate2::{Compression, FlushCompress, FlushDecompress, Status};
 use ssh_packet::{arch::NameList, trans::KexInit};
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, EnumString};
 
 use crate::{
     Error, Result,
@@ -26,14 +25,12 @@ impl Negociate<Server> for Compress {
     }
 }
 
-// TODO: (compliance) Fix compression algorithms, not working right now.
-
 /// SSH compression algorithms.
 #[non_exhaustive]
-#[derive(Debug, Clone, Default, PartialEq, EnumString, AsRefStr)]
+#[derive(Debug, Clone, Default, PartialEq, EnumString, AsRefStr, EnumIter)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Compress {
-    /// zlib compression (OpenSSH mode).
+    /// zlib compression (OpenSSH mode), delayed until the user is authenticated.
     #[strum(serialize = "zlib@openssh.com")]
     ZlibOpenssh,
 
@@ -46,32 +43,136 @@ pub enum Compress {
 }
 
 impl Compress {
-    pub(crate) fn decompress(&self, buf: Vec<u8>) -> Result<Vec<u8>> {
-        match self {
-            Self::ZlibOpenssh | Self::Zlib => {
-                let mut buffer = Vec::with_capacity(buf.len());
-                let decoder = libflate::zlib::Decoder::new(std::io::Cursor::new(buf))?;
+    /// Whether the compression is delayed until the user is authenticated.
+    pub fn delayed(&self) -> bool {
+        matches!(self, Self::ZlibOpenssh)
+    }
+}
 
-                decoder
-                    .take(ssh_packet::Packet::MAX_SIZE as u64)
-                    .read_to_end(&mut buffer)?;
+/// The zlib compression level, the same as OpenSSH.
+const LEVEL: u32 = 6;
 
-                Ok(buffer)
-            }
-            Self::None => Ok(buf),
+/// The compression state, holding a single zlib stream for all the packets of a direction.
+#[derive(Debug, Default)]
+pub enum CompressState {
+    /// Compression awaiting the activation, passing through the packets.
+    Delayed,
+    Zlib(Box<flate2::Compress>),
+    #[default]
+    None,
+}
+
+impl CompressState {
+    pub fn new(compress: &Compress) -> Self {
+        match compress {
+            Compress::ZlibOpenssh => Self::Delayed,
+            Compress::Zlib => Self::zlib(),
+            Compress::None => Self::None,
+        }
+    }
+
+    fn zlib() -> Self {
+        Self::Zlib(Box::new(flate2::Compress::new(
+            Compression::new(LEVEL),
+            true,
+        )))
+    }
+
+    /// Activate a delayed compression, for all the subsequent packets.
+    pub fn activate(&mut self) {
+        if let Self::Delayed = self {
+            *self = Self::zlib();
         }
     }
 
-    pub(crate) fn compress(&self, buf: &[u8]) -> Result<Vec<u8>> {
+    /// Compress the `buf` as a sequence of blocks ending with a partial flush.
+    pub fn compress(&mut self, buf: &[u8]) -> Result<Vec<u8>> {
         match self {
-            Self::ZlibOpenssh | Self::Zlib => {
-                let mut encoder = libflate::zlib::Encoder::new(Vec::with_capacity(buf.len()))?;
+            Self::Zlib(state) => {
+                let mut output = Vec::with_capacity(buf.len() + 64);
+                let offset = state.total_in();
+
+                loop {
+                    let consumed = (state.total_in() - offset) as usize;
+                    state
+                        .compress_vec(&buf[consumed..], &mut output, FlushCompress::Partial)
+                        .map_err(std::io::Error::from)?;
+
+                    // The flush is complete when the output wasn't exhausted.
+                    if output.len() < output.capacity() {
+                        break Ok(output);
+                    }
+
+                    output.reserve(output.capacity());
+                }
+            }
+            Self::Delayed | Self::None => Ok(buf.into()),
+        }
+    }
+}
 
-                encoder.write_all(buf)?;
+/// The decompression state, holding a single zlib stream for all the packets of a direction.
+#[derive(Debug, Default)]
+pub enum DecompressState {
+    /// Decompression awaiting the activation, passing through the packets.
+    Delayed,
+    Zlib(Box<flate2::Decompress>),
+    #[default]
+    None,
+}
 
-                Ok(encoder.finish().into_result()?)
+impl DecompressState {
+    pub fn new(compress: &Compress) -> Self {
+        match compress {
+            Compress::ZlibOpenssh => Self::Delayed,
+            Compress::Zlib => Self::zlib(),
+            Compress::None => Self::None,
+        }
+    }
+
+    fn zlib() -> Self {
+        Self::Zlib(Box::new(flate2::Decompress::new(true)))
+    }
+
+    /// Activate a delayed decompression, for all the subsequent packets.
+    pub fn activate(&mut self) {
+        if let Self::Delayed = self {
+            *self = Self::zlib();
+        }
+    }
+
+    /// Decompress the `buf`, limiting the output to [`ssh_packet::Packet::MAX_SIZE`].
+    pub fn decompress(&mut self, buf: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            Self::Zlib(state) => {
+                let mut output = Vec::with_capacity(buf.len().max(64) * 2);
+                let offset = state.total_in();
+
+                loop {
+                    let consumed = (state.total_in() - offset) as usize;
+                    let status = state
+                        .decompress_vec(&buf[consumed..], &mut output, FlushDecompress::Sync)
+                        .map_err(std::io::Error::from)?;
+
+                    if output.len() > ssh_packet::Packet::MAX_SIZE {
+                        break Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!(
+                                "decompressed payload size too large, > {}",
+                                ssh_packet::Packet::MAX_SIZE
+                            ),
+                        ))?;
+                    }
+
+                    // The packet is complete when the output wasn't exhausted by the input.
+                    if output.len() < output.capacity() || matches!(status, Status::StreamEnd) {
+                        break Ok(output);
+                    }
+
+                    output.reserve(output.capacity());
+                }
             }
-            Self::None => Ok(buf.into()),
+            Self::Delayed | Self::None => Ok(buf),
         }
     }
 }
@@ -4,13 +4,16 @@ use hmac::{
     digest::{DynDigest, MacError},
 };
 use ssh_packet::{arch::NameList, trans::KexInit};
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, IntoEnumIterator};
 
 use crate::{
     Error, Result,
     side::{client::Client, server::Server},
 };
 
+mod umac;
+use umac::Umac;
+
 impl super::Negociate<Client> for Hmac {
     const ERR: Error = Error::NoCommonHmac;
 
@@ -29,9 +32,17 @@ impl super::Negociate<Server> for Hmac {
 
 /// SSH hmac algorithms.
 #[non_exhaustive]
-#[derive(Debug, Clone, EnumString, AsRefStr)]
+#[derive(Debug, Clone, PartialEq, AsRefStr, EnumIter)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Hmac {
+    /// UMAC with 128-bit tag on encrypted message.
+    #[strum(serialize = "umac-128-etm@openssh.com")]
+    Umac128ETM,
+
+    /// UMAC with 64-bit tag on encrypted message.
+    #[strum(serialize = "umac-64-etm@openssh.com")]
+    Umac64ETM,
+
     /// HMAC with sha-2-512 digest on encrypted message.
     #[strum(serialize = "hmac-sha2-512-etm@openssh.com")]
     HmacSha512ETM,
@@ -48,24 +59,59 @@ pub enum Hmac {
     #[strum(serialize = "hmac-sha2-256")]
     HmacSha256,
 
+    /// UMAC with 128-bit tag.
+    #[strum(serialize = "umac-128@openssh.com")]
+    Umac128,
+
+    /// UMAC with 64-bit tag.
+    #[strum(serialize = "umac-64@openssh.com")]
+    Umac64,
+
     /// HMAC with sha-1 digest on encrypted message.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     #[strum(serialize = "hmac-sha1-etm@openssh.com")]
     HmacSha1ETM,
 
     /// HMAC with sha-1 digest.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     HmacSha1,
 
     /// HMAC with md5 digest on encrypted message.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     #[strum(serialize = "hmac-md5-etm@openssh.com")]
     HmacMd5ETM,
 
     /// HMAC with md5 digest.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
     HmacMd5,
 
-    /// No HMAC algorithm.
+    /// No HMAC algorithm, used internally with the authenticated ciphers,
+    /// and only negociable with the peer with the `insecure` feature.
     None,
 }
 
+impl Hmac {
+    /// Whether the algorithm may be negociated with the peer.
+    pub(crate) fn negociable(&self) -> bool {
+        cfg!(feature = "insecure") || *self != Self::None
+    }
+}
+
+impl std::str::FromStr for Hmac {
+    type Err = strum::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter()
+            .filter(Self::negociable)
+            .find(|hmac| hmac.as_ref() == s)
+            .ok_or(strum::ParseError::VariantNotFound)
+    }
+}
+
 pub type HmacBuf = heapless::Vec<u8, 64>;
 
 #[derive(Debug, Default)]
@@ -78,8 +124,12 @@ pub struct State {
 enum Core {
     HmacSha512(hmac::HmacReset<sha2::Sha512>),
     HmacSha256(hmac::HmacReset<sha2::Sha256>),
+    #[cfg(feature = "insecure")]
     HmacSha1(hmac::HmacReset<sha1::Sha1>),
+    #[cfg(feature = "insecure")]
     HmacMd5(hmac::HmacReset<md5::Md5>),
+    Umac128(Box<Umac<4>>),
+    Umac64(Box<Umac<2>>),
     #[default]
     None,
 }
@@ -93,8 +143,11 @@ impl State {
     ) -> Self {
         const SHA512_KS: usize = 64;
         const SHA256_KS: usize = 32;
+        #[cfg(feature = "insecure")]
         const SHA1_KS: usize = 20;
+        #[cfg(feature = "insecure")]
         const MD5_KS: usize = 16;
+        const UMAC_KS: usize = 16;
 
         fn new<const S: usize, H: Digest + FixedOutputReset, M: KeyInit>(
             kind: u8,
@@ -108,10 +161,14 @@ impl State {
         }
 
         Self {
-            etm: matches!(
-                hmac,
-                Hmac::HmacSha512ETM | Hmac::HmacSha256ETM | Hmac::HmacSha1ETM | Hmac::HmacMd5ETM
-            ),
+            etm: match hmac {
+                Hmac::Umac128ETM | Hmac::Umac64ETM | Hmac::HmacSha512ETM | Hmac::HmacSha256ETM => {
+                    true
+                }
+                #[cfg(feature = "insecure")]
+                Hmac::HmacSha1ETM | Hmac::HmacMd5ETM => true,
+                _ => false,
+            },
 
             core: match hmac {
                 Hmac::HmacSha512ETM | Hmac::HmacSha512 => {
@@ -120,12 +177,22 @@ impl State {
                 Hmac::HmacSha256ETM | Hmac::HmacSha256 => {
                     Core::HmacSha256(new::<SHA256_KS, H, _>(K, secret, hash, session_id))
                 }
+                #[cfg(feature = "insecure")]
                 Hmac::HmacSha1ETM | Hmac::HmacSha1 => {
                     Core::HmacSha1(new::<SHA1_KS, H, _>(K, secret, hash, session_id))
                 }
+                #[cfg(feature = "insecure")]
                 Hmac::HmacMd5ETM | Hmac::HmacMd5 => {
                     Core::HmacMd5(new::<MD5_KS, H, _>(K, secret, hash, session_id))
                 }
+                Hmac::Umac128ETM | Hmac::Umac128 => Core::Umac128(Box::new(Umac::new(
+                    super::kdf::<UMAC_KS, H>(K, secret, hash, session_id),
+                ))),
+                Hmac::Umac64ETM | Hmac::Umac64 => {
+                    Core::Umac64(Box::new(Umac::new(super::kdf::<UMAC_KS, H>(
+                        K, secret, hash, session_id,
+                    ))))
+                }
                 Hmac::None => Core::None,
             },
         }
@@ -143,8 +210,14 @@ impl State {
         match &mut self.core {
             Core::HmacSha512(state) => compute(state, seq, buf),
             Core::HmacSha256(state) => compute(state, seq, buf),
+            #[cfg(feature = "insecure")]
             Core::HmacSha1(state) => compute(state, seq, buf),
+            #[cfg(feature = "insecure")]
             Core::HmacMd5(state) => compute(state, seq, buf),
+            Core::Umac128(state) => HmacBuf::from_slice(&state.compute(nonce(seq), buf))
+                .expect("UMAC output is bigger than the alloted storage"),
+            Core::Umac64(state) => HmacBuf::from_slice(&state.compute(nonce(seq), buf))
+                .expect("UMAC output is bigger than the alloted storage"),
             Core::None => Default::default(),
         }
     }
@@ -165,8 +238,12 @@ impl State {
         match &mut self.core {
             Core::HmacSha512(state) => verify(state, seq, buf, mac),
             Core::HmacSha256(state) => verify(state, seq, buf, mac),
+            #[cfg(feature = "insecure")]
             Core::HmacSha1(state) => verify(state, seq, buf, mac),
+            #[cfg(feature = "insecure")]
             Core::HmacMd5(state) => verify(state, seq, buf, mac),
+            Core::Umac128(state) => umac::verify(&state.compute(nonce(seq), buf), mac),
+            Core::Umac64(state) => umac::verify(&state.compute(nonce(seq), buf), mac),
             Core::None => Ok(()),
         }
     }
@@ -175,8 +252,12 @@ impl State {
         match &self.core {
             Core::HmacSha512(state) => state.output_size(),
             Core::HmacSha256(state) => state.output_size(),
+            #[cfg(feature = "insecure")]
             Core::HmacSha1(state) => state.output_size(),
+            #[cfg(feature = "insecure")]
             Core::HmacMd5(state) => state.output_size(),
+            Core::Umac128(_) => Umac::<4>::SIZE,
+            Core::Umac64(_) => Umac::<2>::SIZE,
             Core::None => 0,
         }
     }
@@ -185,3 +266,8 @@ impl State {
         self.etm
     }
 }
+
+/// UMAC uses the packet sequence number, extended to 64 bits, as the nonce.
+fn nonce(seq: u32) -> [u8; 8] {
+    (seq as u64).to_be_bytes()
+}
@@ -0,0 +1,418 @@
+//! The UMAC message authentication code, as per RFC 4418,
+//! with the `ITERS` parameter producing tags of `4 * ITERS` bytes.
+
+use aes::Aes128;
+use cipher::{BlockCipherEncrypt, KeyInit};
+use digest::MacError;
+use subtle::{ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater};
+
+/// Prime modulus of the 36-bits arithmetic of the L3-HASH.
+const P36: u64 = (1 << 36) - 5;
+
+/// Prime modulus of the 64-bits arithmetic of the L2-HASH.
+const P64: u64 = 0u64.wrapping_sub(59);
+
+/// Prime modulus of the 128-bits arithmetic of the L2-HASH.
+const P128: u128 = 0u128.wrapping_sub(159);
+
+/// Size in bytes of the chunks processed by the L1-HASH.
+const L1_CHUNK: usize = 1024;
+
+/// Size in bytes of the L1-HASH output processed by the 64-bits polynomial of the L2-HASH.
+const L2_SPLIT: usize = 1 << 17;
+
+pub struct Umac<const ITERS: usize> {
+    pdf: Aes128,
+    l1: Vec<u32>,
+    l2: [(u64, u128); ITERS],
+    l3: [([u64; 8], u32); ITERS],
+}
+
+impl<const ITERS: usize> Umac<ITERS> {
+    /// Size in bytes of the produced tags.
+    pub const SIZE: usize = 4 * ITERS;
+
+    pub fn new(key: [u8; 16]) -> Self {
+        let aes = Aes128::new(&key.into());
+
+        let pdf = Aes128::new_from_slice(&kdf(&aes, 0, 16)).expect("the key is 16 bytes long");
+
+        let l1 = kdf(&aes, 1, L1_CHUNK + (ITERS - 1) * 16)
+            .chunks_exact(4)
+            .map(|word| u32::from_be_bytes(word.try_into().expect("the word is 4 bytes long")))
+            .collect();
+
+        let l2 = kdf(&aes, 2, ITERS * 24);
+        let l2 = std::array::from_fn(|i| {
+            let key = &l2[i * 24..(i + 1) * 24];
+
+            (
+                u64::from_be_bytes(key[..8].try_into().expect("the key is 8 bytes long"))
+                    & 0x01ffffff01ffffff,
+                u128::from_be_bytes(key[8..].try_into().expect("the key is 16 bytes long"))
+                    & 0x01ffffff01ffffff01ffffff01ffffff,
+            )
+        });
+
+        let (l31, l32) = (kdf(&aes, 3, ITERS * 64), kdf(&aes, 4, ITERS * 4));
+        let l3 = std::array::from_fn(|i| {
+            (
+                std::array::from_fn(|j| {
+                    let offset = i * 64 + j * 8;
+
+                    reduce36(u64::from_be_bytes(
+                        l31[offset..offset + 8]
+                            .try_into()
+                            .expect("the key is 8 bytes long"),
+                    ))
+                }),
+                u32::from_be_bytes(
+                    l32[i * 4..(i + 1) * 4]
+                        .try_into()
+                        .expect("the key is 4 bytes long"),
+                ),
+            )
+        });
+
+        Self { pdf, l1, l2, l3 }
+    }
+
+    /// Compute the tag of the `message` under the 8-bytes `nonce`.
+    pub fn compute(&self, nonce: [u8; 8], message: &[u8]) -> heapless::Vec<u8, 16> {
+        (0..ITERS)
+            .flat_map(|i| self.hash(i, message).to_be_bytes())
+            .zip(self.pad(nonce))
+            .map(|(hash, pad)| hash ^ pad)
+            .collect()
+    }
+
+    /// The pad-derived function, encrypting the `nonce` to mask the hash.
+    fn pad(&self, mut nonce: [u8; 8]) -> [u8; 16] {
+        let index = if Self::SIZE <= 8 {
+            let index = nonce[7] % (16 / Self::SIZE as u8);
+            nonce[7] ^= index;
+
+            index as usize
+        } else {
+            0
+        };
+
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&nonce);
+
+        let mut block = block.into();
+        self.pdf.encrypt_block(&mut block);
+
+        let mut block = <[u8; 16]>::from(block);
+        block.rotate_left(index * Self::SIZE);
+
+        block
+    }
+
+    /// The three-layer hash of the `message` for the `iter`-th iteration.
+    fn hash(&self, iter: usize, message: &[u8]) -> u32 {
+        let count = message.len().div_ceil(L1_CHUNK).max(1);
+        let mut hashes = self.l1(iter, message);
+
+        let hash = if count == 1 {
+            hashes.next().unwrap_or_default() as u128
+        } else {
+            self.l2(iter, hashes, count)
+        };
+
+        self.l3(iter, hash)
+    }
+
+    /// The first-layer hash, compressing the message with the NH function by chunks of 1024 bytes.
+    fn l1<'m>(&'m self, iter: usize, message: &'m [u8]) -> impl Iterator<Item = u64> + 'm {
+        let key = &self.l1[iter * 4..iter * 4 + L1_CHUNK / 4];
+
+        // The empty message is hashed as a single empty chunk.
+        let empty = message.is_empty().then_some(&[][..]);
+
+        message.chunks(L1_CHUNK).chain(empty).map(move |chunk| {
+            let (blocks, tail) = chunk.split_at(chunk.len() / 32 * 32);
+
+            let mut hash = nh(key, blocks);
+            if !tail.is_empty() || chunk.is_empty() {
+                let mut padded = [0u8; 32];
+                padded[..tail.len()].copy_from_slice(tail);
+
+                hash = hash.wrapping_add(nh(&key[blocks.len() / 4..], &padded));
+            }
+
+            hash.wrapping_add(chunk.len() as u64 * 8)
+        })
+    }
+
+    /// The second-layer hash, compressing the `count` first-layer hashes with polynomials.
+    fn l2(&self, iter: usize, mut hashes: impl Iterator<Item = u64>, count: usize) -> u128 {
+        let (k64, k128) = self.l2[iter];
+
+        let y = poly64(k64, hashes.by_ref().take(L2_SPLIT / 8)) as u128;
+
+        if count <= L2_SPLIT / 8 {
+            y
+        } else {
+            let words = std::iter::from_fn(move || {
+                let hi = hashes.next()?;
+
+                Some(match hashes.next() {
+                    Some(lo) => ((hi as u128) << 64) | lo as u128,
+                    None => ((hi as u128) << 64) | (0x80 << 56),
+                })
+            });
+            let end = (count - L2_SPLIT / 8)
+                .is_multiple_of(2)
+                .then_some(0x80 << 120);
+
+            poly128(k128, std::iter::once(y).chain(words).chain(end))
+        }
+    }
+
+    /// The third-layer hash, reducing the second-layer hash to 4 bytes.
+    fn l3(&self, iter: usize, hash: u128) -> u32 {
+        let (k1, k2) = &self.l3[iter];
+
+        let y = hash
+            .to_be_bytes()
+            .chunks_exact(2)
+            .zip(k1)
+            .map(|(m, k)| u16::from_be_bytes([m[0], m[1]]) as u64 * k)
+            .sum::<u64>();
+
+        (reduce36(y) as u32) ^ k2
+    }
+}
+
+impl<const ITERS: usize> std::fmt::Debug for Umac<ITERS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Umac").finish_non_exhaustive()
+    }
+}
+
+/// Compare the computed `tag` with the received `mac` in constant-time.
+pub fn verify(tag: &[u8], mac: &[u8]) -> Result<(), MacError> {
+    if tag.ct_eq(mac).into() {
+        Ok(())
+    } else {
+        Err(MacError)
+    }
+}
+
+/// The key-derivation function, producing `size` bytes from the `index`.
+fn kdf(aes: &Aes128, index: u64, size: usize) -> Vec<u8> {
+    (1..=size.div_ceil(16) as u64)
+        .flat_map(|i| {
+            let mut block = [0u8; 16];
+            block[..8].copy_from_slice(&index.to_be_bytes());
+            block[8..].copy_from_slice(&i.to_be_bytes());
+
+            let mut block = block.into();
+            aes.encrypt_block(&mut block);
+
+            <[u8; 16]>::from(block)
+        })
+        .take(size)
+        .collect()
+}
+
+/// The NH universal hash function, over a message whose length is a multiple of 32 bytes.
+fn nh(key: &[u32], message: &[u8]) -> u64 {
+    message
+        .chunks_exact(32)
+        .zip(key.chunks_exact(8))
+        .map(|(block, key)| {
+            let word = |i: usize| {
+                u32::from_le_bytes(
+                    block[i * 4..(i + 1) * 4]
+                        .try_into()
+                        .expect("the word is 4 bytes long"),
+                )
+                .wrapping_add(key[i]) as u64
+            };
+
+            (0..4).fold(0u64, |y, i| {
+                y.wrapping_add(word(i).wrapping_mul(word(i + 4)))
+            })
+        })
+        .fold(0, u64::wrapping_add)
+}
+
+/// The polynomial hash over 64-bits words, modulo [`P64`].
+fn poly64(key: u64, words: impl IntoIterator<Item = u64>) -> u64 {
+    const MAX: u64 = 0u64.wrapping_sub(1 << 32);
+
+    let step = |y: u64, m: u64| reduce64(key as u128 * y as u128 + m as u128);
+
+    words.into_iter().fold(1, |y, m| {
+        // The words outside of the field are hashed as two words, selected in constant-time.
+        u64::conditional_select(
+            &step(y, m),
+            &step(step(y, P64 - 1), m.wrapping_sub(0u64.wrapping_sub(P64))),
+            m.ct_gt(&(MAX - 1)),
+        )
+    })
+}
+
+/// The polynomial hash over 128-bits words, modulo [`P128`].
+fn poly128(key: u128, words: impl IntoIterator<Item = u128>) -> u128 {
+    const MAX: u128 = 0u128.wrapping_sub(1 << 96);
+
+    let step = |y: u128, m: u128| add128(mul128(key, y), m);
+
+    words.into_iter().fold(1, |y, m| {
+        // The words outside of the field are hashed as two words, selected in constant-time.
+        u128::conditional_select(
+            &step(y, m),
+            &step(step(y, P128 - 1), m.wrapping_sub(0u128.wrapping_sub(P128))),
+            m.ct_gt(&(MAX - 1)),
+        )
+    })
+}
+
+/// Reduce `x` modulo [`P36`] in constant-time, given that `2^36 = 5 (mod P36)`.
+fn reduce36(x: u64) -> u64 {
+    const MASK: u64 = (1 << 36) - 1;
+
+    // Two folds bring any 64-bits value below `2^36`.
+    let x = (x >> 36) * 5 + (x & MASK);
+    let x = (x >> 36) * 5 + (x & MASK);
+
+    u64::conditional_select(&x, &x.wrapping_sub(P36), x.ct_gt(&(P36 - 1)))
+}
+
+/// Reduce `x` modulo [`P64`] in constant-time, given that `2^64 = 59 (mod P64)`.
+fn reduce64(x: u128) -> u64 {
+    // Three folds bring any 128-bits value under `2^64`.
+    let x = (x >> 64) * 59 + (x as u64 as u128);
+    let x = (x >> 64) * 59 + (x as u64 as u128);
+    let x = ((x >> 64) * 59 + (x as u64 as u128)) as u64;
+
+    u64::conditional_select(&x, &x.wrapping_sub(P64), x.ct_gt(&(P64 - 1)))
+}
+
+/// Reduce `hi * 2^128 + lo` modulo [`P128`] in constant-time, given that `2^128 = 159 (mod P128)`.
+fn reduce128(hi: u128, lo: u128) -> u128 {
+    let fold = |(hi, lo): (u128, u128)| {
+        let (h, l) = widening128(hi, 159);
+        let (sum, carry) = lo.overflowing_add(l);
+
+        (h + carry as u128, sum)
+    };
+
+    // Three folds bring any 256-bits value under `2^128`.
+    let (_, lo) = fold(fold(fold((hi, lo))));
+
+    u128::conditional_select(&lo, &lo.wrapping_sub(P128), lo.ct_gt(&(P128 - 1)))
+}
+
+fn add128(a: u128, b: u128) -> u128 {
+    let (sum, carry) = a.overflowing_add(b);
+
+    reduce128(carry as u128, sum)
+}
+
+fn mul128(a: u128, b: u128) -> u128 {
+    let (hi, lo) = widening128(a, b);
+
+    reduce128(hi, lo)
+}
+
+/// The full 256-bits product of `a` and `b`, as it's high and low halves.
+fn widening128(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a1, a0) = (a >> 64, a & MASK);
+    let (b1, b0) = (b >> 64, b & MASK);
+
+    let (lo, mid0, mid1, hi) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+
+    let (mid, carry) = mid0.overflowing_add(mid1);
+    let (lo, carry_lo) = lo.overflowing_add(mid << 64);
+
+    (
+        hi + (mid >> 64) + ((carry as u128) << 64) + carry_lo as u128,
+        lo,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8; 16] = b"abcdefghijklmnop";
+    const NONCE: &[u8; 8] = b"bcdefghi";
+
+    /// The RFC 4418 test vectors, with the UMAC-128 tags from the Nettle test suite
+    /// since the RFC lists them without their last 4 bytes.
+    #[test]
+    fn it_matches_the_rfc_test_vectors() {
+        let cases: [(Vec<u8>, &str, &str, &str); 8] = [
+            (
+                vec![],
+                "113145FB",
+                "6E155FAD26900BE1",
+                "32FEDB100C79AD58F07FF7643CC60465",
+            ),
+            (
+                vec![b'a'; 3],
+                "3B91D102",
+                "44B5CB542F220104",
+                "185E4FE905CBA7BD85E4C2DC3D117D8D",
+            ),
+            (
+                vec![b'a'; 1 << 10],
+                "599B350B",
+                "26BF2F5D60118BD9",
+                "7A54ABE04AF82D60FB298C3CBD195BCB",
+            ),
+            (
+                vec![b'a'; 1 << 15],
+                "58DCF532",
+                "27F8EF643B0D118D",
+                "7B136BD911E4B734286EF2BE501F2C3C",
+            ),
+            (
+                vec![b'a'; 1 << 20],
+                "DB6364D1",
+                "A4477E87E9F55853",
+                "F8ACFA3AC31CFEEA047F7B115B03BEF5",
+            ),
+            (
+                vec![b'a'; 1 << 25],
+                "85EE5CAE",
+                "FACA46F856E9B45F",
+                "A621C2457C0012E64F3FDAE9E7E1870C",
+            ),
+            (
+                b"abc".to_vec(),
+                "ABF3A3A0",
+                "D4D7B9F6BD4FBFCF",
+                "883C3D4B97A61976FFCF232308CBA5A5",
+            ),
+            (
+                b"abc".repeat(500),
+                "ABEB3C8B",
+                "D4CF26DDEFD5C01A",
+                "8824A260C53C66A36C9260A62CB83AA1",
+            ),
+        ];
+
+        let (umac32, umac64, umac128) = (
+            Umac::<1>::new(*KEY),
+            Umac::<2>::new(*KEY),
+            Umac::<4>::new(*KEY),
+        );
+
+        for (message, tag32, tag64, tag128) in cases {
+            let hex = |tag: heapless::Vec<u8, 16>| {
+                tag.iter().map(|b| format!("{b:02X}")).collect::<String>()
+            };
+
+            assert_eq!(hex(umac32.compute(*NONCE, &message)), tag32);
+            assert_eq!(hex(umac64.compute(*NONCE, &message)), tag64);
+            assert_eq!(hex(umac128.compute(*NONCE, &message)), tag128);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crypto_bigint::{
+    BoxedUint, Odd,
+    modular::{BoxedMontyForm, BoxedMontyParams},
+};
+use rand::Rng;
+use secrecy::{ExposeSecret, SecretBox, SecretSlice};
+use signature::SignatureEncoding;
+use signature::digest::{Digest, FixedOutputReset};
+use ssh_packet::{
+    arch::MpInt,
+    trans::{KexdhInit, KexdhReply},
+};
+
+use super::{KexMeta, exchange};
+use crate::{
+    Error, Pipe, Result,
+    stream::{
+        Stream, Transport,
+        algorithm::key::{self, HostKey, PeerHostKey},
+    },
+};
+
+/// A finite-field group for the Diffie-Hellman key-exchange, with `2` as it's generator.
+pub struct Group {
+    bits: u32,
+    prime: &'static str,
+}
+
+impl Group {
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn prime(&self) -> BoxedUint {
+        BoxedUint::from_be_hex(self.prime, self.bits)
+            .into_option()
+            .expect("the group's prime is not valid hexadecimal")
+    }
+}
+
+/// The Oakley Group 2, defined in <https://datatracker.ietf.org/doc/html/rfc2409#section-6.2>.
+#[cfg(feature = "insecure")]
+pub const GROUP1: Group = Group {
+    bits: 1024,
+    prime: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF",
+    ),
+};
+
+/// The 2048-bit MODP Group, defined in <https://datatracker.ietf.org/doc/html/rfc3526#section-3>.
+pub const GROUP14: Group = Group {
+    bits: 2048,
+    prime: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+    ),
+};
+
+/// The 4096-bit MODP Group, defined in <https://datatracker.ietf.org/doc/html/rfc3526#section-5>.
+pub const GROUP16: Group = Group {
+    bits: 4096,
+    prime: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33",
+        "A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+        "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864",
+        "D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2",
+        "08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D7",
+        "88719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8",
+        "DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2",
+        "233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA9",
+        "93B4EA988D8FDDC186FFB7DC90A6C08F4DF435C934063199FFFFFFFFFFFFFFFF",
+    ),
+};
+
+/// The 8192-bit MODP Group, defined in <https://datatracker.ietf.org/doc/html/rfc3526#section-7>.
+pub const GROUP18: Group = Group {
+    bits: 8192,
+    prime: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33",
+        "A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+        "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864",
+        "D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2",
+        "08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D7",
+        "88719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8",
+        "DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2",
+        "233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA9",
+        "93B4EA988D8FDDC186FFB7DC90A6C08F4DF435C93402849236C3FAB4D27C7026",
+        "C1D4DCB2602646DEC9751E763DBA37BDF8FF9406AD9E530EE5DB382F413001AE",
+        "B06A53ED9027D831179727B0865A8918DA3EDBEBCF9B14ED44CE6CBACED4BB1B",
+        "DB7F1447E6CC254B332051512BD7AF426FB8F401378CD2BF5983CA01C64B92EC",
+        "F032EA15D1721D03F482D7CE6E74FEF6D55E702F46980C82B5A84031900B1C9E",
+        "59E7C97FBEC7E8F323A97A7E36CC88BE0F1D45B7FF585AC54BD407B22B4154AA",
+        "CC8F6D7EBF48E1D814CC5ED20F8037E0A79715EEF29BE32806A1D58BB7C5DA76",
+        "F550AA3D8A1FBFF0EB19CCB1A313D55CDA56C9EC2EF29632387FE8D76E3C0468",
+        "043E8F663F4860EE12BF2D5B0B7474D6E694F91E6DBE115974A3926F12FEE5E4",
+        "38777CB6A932DF8CD8BEC4D073B931BA3BC832B68D9DD300741FA7BF8AFC47ED",
+        "2576F6936BA424663AAB639C5AE4F5683423B4742BF1C978238F16CBE39D652D",
+        "E3FDB8BEFC848AD922222E04A4037C0713EB57A81A23F0C73473FC646CEA306B",
+        "4BCBC8862F8385DDFA9D4B7FA2C087E879683303ED5BDD3A062B3CF5B3A278A6",
+        "6D2A13F83F44F82DDF310EE074AB6A364597E899A0255DC164F31CC50846851D",
+        "F9AB48195DED7EA1B1D510BD7EE74D73FAF36BC31ECFA268359046F4EB879F92",
+        "4009438B481C6CD7889A002ED5EE382BC9190DA6FC026E479558E4475677E9AA",
+        "9E3050E2765694DFC81F56E880B96E7160C980DD98EDD3DFFFFFFFFFFFFFFFFF",
+    ),
+};
+
+/// The ephemeral state of a Diffie-Hellman key-exchange in the group defined by `p` and `g`.
+pub struct Ephemeral {
+    p: BoxedUint,
+    g: BoxedMontyForm,
+    x: BoxedUint,
+}
+
+impl Ephemeral {
+    /// Generate an ephemeral secret exponent for the provided group,
+    /// sized to twice the digest output as recommended for the derived key sizes.
+    pub fn new<H: Digest>(p: BoxedUint, g: &[u8]) -> Result<Self> {
+        let g = parse(&p, g)?;
+        let params =
+            BoxedMontyParams::new(Odd::new(p.clone()).into_option().ok_or(Error::KexError)?);
+        let g = BoxedMontyForm::new(g, &params);
+
+        let bits = (<H as Digest>::output_size() as u32 * 16).max(512);
+        let mut x = vec![0; bits as usize / 8];
+        rand::rng().fill_bytes(&mut x);
+        let x = BoxedUint::from_be_slice(&x, bits).map_err(|_| Error::KexError)?;
+
+        Ok(Self { p, g, x })
+    }
+
+    /// Compute our exchange value, `g^x mod p`.
+    pub fn public(&self) -> Vec<u8> {
+        self.g
+            .pow(&self.x)
+            .retrieve()
+            .to_be_bytes_trimmed_vartime()
+            .into()
+    }
+
+    /// Compute the shared secret from the peer's exchange value, `y^x mod p`.
+    pub fn shared(&self, value: &MpInt) -> Result<SecretSlice<u8>> {
+        let y = BoxedMontyForm::new(parse(&self.p, value.as_ref())?, self.g.params());
+
+        Ok(Vec::from(y.pow(&self.x).retrieve().to_be_bytes_trimmed_vartime()).into())
+    }
+}
+
+/// Parse and validate a group element, as it needs to be in the `]1, p-1[` range.
+fn parse(p: &BoxedUint, value: &[u8]) -> Result<BoxedUint> {
+    if value.first().is_some_and(|byte| *byte >= 0x80) {
+        return Err(Error::KexError);
+    }
+
+    let leading = value.iter().take_while(|byte| **byte == 0).count();
+    let value = BoxedUint::from_be_slice(&value[leading..], p.bits_precision())
+        .map_err(|_| Error::KexError)?;
+
+    let one = BoxedUint::one_with_precision(p.bits_precision());
+    if value <= one || value >= p.wrapping_sub(&one) {
+        return Err(Error::KexError);
+    }
+
+    Ok(value)
+}
+
+pub async fn as_client<H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+    group: &Group,
+) -> Result<(Transport, PeerHostKey)> {
+    let ephemeral = Ephemeral::new::<H>(group.prime(), &[2])?;
+    let e = ephemeral.public();
+
+    stream
+        .send(&KexdhInit {
+            e: MpInt::positive(&e),
+        })
+        .await?;
+
+    let dh: KexdhReply = stream.recv().await?.to()?;
+
+    let secret = ephemeral.shared(&dh.f)?;
+    let secret = SecretBox::new(MpInt::positive(secret.expose_secret()).into());
+
+    let k_s = PeerHostKey::decode(&dh.k_s)?;
+    let hash = exchange::Dh {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: dh.k_s,
+        e: MpInt::positive(&e),
+        f: dh.f,
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    k_s.verify(
+        &key::negociate(client.kexinit, server.kexinit)?,
+        &hash,
+        dh.signature.as_ref(),
+    )?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok((
+        Transport::as_client::<H>(
+            client,
+            server,
+            secret.expose_secret().as_ref(),
+            &hash,
+            session_id,
+        ),
+        k_s,
+    ))
+}
+
+pub async fn as_server<H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+    key: HostKey<'_>,
+    group: &Group,
+) -> Result<Transport> {
+    let dh: KexdhInit = stream.recv().await?.to()?;
+
+    let ephemeral = Ephemeral::new::<H>(group.prime(), &[2])?;
+    let f = ephemeral.public();
+
+    let secret = ephemeral.shared(&dh.e)?;
+    let secret = SecretBox::new(MpInt::positive(secret.expose_secret()).into());
+
+    let k_s = key.blob()?;
+
+    let hash = exchange::Dh {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: k_s.as_slice().into(),
+        e: dh.e,
+        f: MpInt::positive(&f),
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    let signature = key.sign(&hash).await?;
+
+    stream
+        .send(&KexdhReply {
+            k_s: k_s.into(),
+            f: MpInt::positive(&f),
+            signature: signature.to_vec().into(),
+        })
+        .await?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok(Transport::as_server::<H>(
+        server,
+        client,
+        secret.expose_secret().as_ref(),
+        &hash,
+        session_id,
+    ))
+}
@@ -0,0 +1,237 @@
+use p256::elliptic_curve::Generate;
+use rand::Rng;
+use secrecy::{ExposeSecret, SecretBox, SecretSlice};
+use signature::SignatureEncoding;
+use signature::digest::{Digest, FixedOutputReset};
+use ssh_packet::{
+    arch::MpInt,
+    crypto::exchange,
+    trans::{KexEcdhInit, KexEcdhReply},
+};
+
+use super::KexMeta;
+use crate::{
+    Error, Pipe, Result,
+    stream::{
+        Stream, Transport,
+        algorithm::key::{self, HostKey, PeerHostKey},
+    },
+};
+
+/// An elliptic-curve usable in the ECDH key-exchange.
+pub trait Curve {
+    /// The ephemeral secret of our side of the exchange.
+    type Secret;
+
+    /// Generate an ephemeral secret along with it's encoded public point.
+    fn generate() -> (Self::Secret, Vec<u8>);
+
+    /// Compute the shared secret from the peer's encoded public point.
+    fn shared(secret: Self::Secret, public: &[u8]) -> Result<SecretSlice<u8>>;
+}
+
+/// The Curve25519 elliptic-curve, as per RFC 8731.
+pub struct X25519;
+
+impl Curve for X25519 {
+    type Secret = x25519_dalek::EphemeralSecret;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        let secret = x25519_dalek::EphemeralSecret::random_from_rng(&mut rand::rng());
+        let public = x25519_dalek::PublicKey::from(&secret);
+
+        (secret, public.as_bytes().to_vec())
+    }
+
+    fn shared(secret: Self::Secret, public: &[u8]) -> Result<SecretSlice<u8>> {
+        let public = x25519_dalek::PublicKey::from(
+            <[u8; 32]>::try_from(public).map_err(|_| Error::KexError)?,
+        );
+
+        let shared = secret.diffie_hellman(&public);
+
+        // Reject low-order points, which yield an all-zero shared secret.
+        if !shared.was_contributory() {
+            return Err(Error::KexError);
+        }
+
+        Ok(shared.as_bytes().to_vec().into())
+    }
+}
+
+impl Curve for p256::NistP256 {
+    type Secret = p256::ecdh::EphemeralSecret;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        let secret = Self::Secret::generate_from_rng(&mut rand::rng());
+        let public = secret.public_key().to_sec1_bytes();
+
+        (secret, public.into())
+    }
+
+    fn shared(secret: Self::Secret, public: &[u8]) -> Result<SecretSlice<u8>> {
+        let public = p256::PublicKey::from_sec1_bytes(public).map_err(|_| Error::KexError)?;
+
+        Ok(secret
+            .diffie_hellman(&public)
+            .raw_secret_bytes()
+            .to_vec()
+            .into())
+    }
+}
+
+impl Curve for p384::NistP384 {
+    type Secret = p384::ecdh::EphemeralSecret;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        let secret = Self::Secret::generate_from_rng(&mut rand::rng());
+        let public = secret.public_key().to_sec1_bytes();
+
+        (secret, public.into())
+    }
+
+    fn shared(secret: Self::Secret, public: &[u8]) -> Result<SecretSlice<u8>> {
+        let public = p384::PublicKey::from_sec1_bytes(public).map_err(|_| Error::KexError)?;
+
+        Ok(secret
+            .diffie_hellman(&public)
+            .raw_secret_bytes()
+            .to_vec()
+            .into())
+    }
+}
+
+impl Curve for p521::NistP521 {
+    type Secret = p521::NonZeroScalar;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        // The `p521` crate lags behind in the `rand_core` versions,
+        // so the scalar is sampled by rejection from our own random source.
+        let secret = std::iter::repeat_with(|| {
+            let mut bytes = [0u8; 66];
+            rand::rng().fill_bytes(&mut bytes);
+            bytes[0] &= 0x01;
+
+            p521::NonZeroScalar::try_from(bytes.as_slice()).ok()
+        })
+        .find_map(|scalar| scalar)
+        .expect("The random scalar sampling is unbounded");
+        let public = p521::PublicKey::from_secret_scalar(&secret).to_sec1_bytes();
+
+        (secret, public.into())
+    }
+
+    fn shared(secret: Self::Secret, public: &[u8]) -> Result<SecretSlice<u8>> {
+        let public = p521::PublicKey::from_sec1_bytes(public).map_err(|_| Error::KexError)?;
+
+        Ok(p521::ecdh::diffie_hellman(secret, public.as_affine())
+            .raw_secret_bytes()
+            .to_vec()
+            .into())
+    }
+}
+
+/// Encode the shared secret as a [`MpInt`], which requires stripping it's leading zeroes.
+fn secret(shared: &SecretSlice<u8>) -> SecretBox<MpInt<'static>> {
+    let shared = shared.expose_secret();
+    let leading = shared.iter().take_while(|byte| **byte == 0).count();
+
+    SecretBox::new(MpInt::from_bytes(MpInt::positive(&shared[leading..]).as_ref().to_vec()).into())
+}
+
+pub async fn as_client<C: Curve, H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+) -> Result<(Transport, PeerHostKey)> {
+    let (e_c, q_c) = C::generate();
+
+    stream
+        .send(&KexEcdhInit {
+            q_c: q_c.as_slice().into(),
+        })
+        .await?;
+
+    let ecdh: KexEcdhReply = stream.recv().await?.to()?;
+
+    let secret = secret(&C::shared(e_c, &ecdh.q_s)?);
+
+    let k_s = PeerHostKey::decode(&ecdh.k_s)?;
+    let hash = exchange::Ecdh {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: ecdh.k_s,
+        q_c: q_c.as_slice().into(),
+        q_s: ecdh.q_s,
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    k_s.verify(
+        &key::negociate(client.kexinit, server.kexinit)?,
+        &hash,
+        ecdh.signature.as_ref(),
+    )?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok((
+        Transport::as_client::<H>(
+            client,
+            server,
+            secret.expose_secret().as_ref(),
+            &hash,
+            session_id,
+        ),
+        k_s,
+    ))
+}
+
+pub async fn as_server<C: Curve, H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+    key: HostKey<'_>,
+) -> Result<Transport> {
+    let ecdh: KexEcdhInit = stream.recv().await?.to()?;
+
+    let (e_s, q_s) = C::generate();
+
+    let secret = secret(&C::shared(e_s, &ecdh.q_c)?);
+
+    let k_s = key.blob()?;
+
+    let hash = exchange::Ecdh {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: k_s.as_slice().into(),
+        q_c: ecdh.q_c,
+        q_s: q_s.as_slice().into(),
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    let signature = key.sign(&hash).await?;
+
+    stream
+        .send(&KexEcdhReply {
+            k_s: k_s.into(),
+            q_s: q_s.as_slice().into(),
+            signature: signature.to_vec().into(),
+        })
+        .await?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok(Transport::as_server::<H>(
+        server,
+        client,
+        secret.expose_secret().as_ref(),
+        &hash,
+        session_id,
+    ))
+}
@@ -0,0 +1,154 @@
+//! Exchange hashes missing from [`ssh_packet::crypto::exchange`].
+
+use ssh_packet::{
+    arch,
+    binrw::{self, binwrite},
+    crypto::Lengthed,
+    trans,
+};
+
+/// The exchange hash for DH, computed as the
+/// hash of the concatenation of the following.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4253#section-8>.
+#[binwrite]
+#[derive(Debug)]
+#[bw(big)]
+pub struct Dh<'b> {
+    /// Client's identification string (`\r` and `\n` excluded).
+    pub v_c: arch::Bytes<'b>,
+
+    /// Server's identification string (`\r` and `\n` excluded).
+    pub v_s: arch::Bytes<'b>,
+
+    /// Payload of the client's `SSH_MSG_KEXINIT` message.
+    pub i_c: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Payload of the server's `SSH_MSG_KEXINIT` message.
+    pub i_s: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Server's public host key.
+    pub k_s: arch::Bytes<'b>,
+
+    /// Client's exchange value.
+    pub e: arch::MpInt<'b>,
+
+    /// Server's exchange value.
+    pub f: arch::MpInt<'b>,
+
+    /// Computed shared secret.
+    pub k: arch::MpInt<'b>,
+}
+
+impl Dh<'_> {
+    /// Produce the exchange hash with the specified digest algorithm.
+    pub fn hash<D: digest::Digest>(&self) -> digest::Output<D> {
+        hash::<D>(self)
+    }
+}
+
+/// The exchange hash for DH group-exchange, computed as the
+/// hash of the concatenation of the following.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4419#section-3>.
+#[binwrite]
+#[derive(Debug)]
+#[bw(big)]
+pub struct DhGex<'b> {
+    /// Client's identification string (`\r` and `\n` excluded).
+    pub v_c: arch::Bytes<'b>,
+
+    /// Server's identification string (`\r` and `\n` excluded).
+    pub v_s: arch::Bytes<'b>,
+
+    /// Payload of the client's `SSH_MSG_KEXINIT` message.
+    pub i_c: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Payload of the server's `SSH_MSG_KEXINIT` message.
+    pub i_s: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Server's public host key.
+    pub k_s: arch::Bytes<'b>,
+
+    /// Minimal size in bits of an acceptable group.
+    pub min: u32,
+
+    /// Preferred size in bits of the group the server will send.
+    pub n: u32,
+
+    /// Maximal size in bits of an acceptable group.
+    pub max: u32,
+
+    /// Safe prime of the group.
+    pub p: arch::MpInt<'b>,
+
+    /// Generator of the group.
+    pub g: arch::MpInt<'b>,
+
+    /// Client's exchange value.
+    pub e: arch::MpInt<'b>,
+
+    /// Server's exchange value.
+    pub f: arch::MpInt<'b>,
+
+    /// Computed shared secret.
+    pub k: arch::MpInt<'b>,
+}
+
+impl DhGex<'_> {
+    /// Produce the exchange hash with the specified digest algorithm.
+    pub fn hash<D: digest::Digest>(&self) -> digest::Output<D> {
+        hash::<D>(self)
+    }
+}
+
+/// The exchange hash for the hybrid post-quantum key-exchanges, computed as the
+/// hash of the concatenation of the following.
+///
+/// see <https://datatracker.ietf.org/doc/html/draft-ietf-sshm-mlkem-hybrid-kex#section-2.4>.
+#[binwrite]
+#[derive(Debug)]
+#[bw(big)]
+pub struct Hybrid<'b> {
+    /// Client's identification string (`\r` and `\n` excluded).
+    pub v_c: arch::Bytes<'b>,
+
+    /// Server's identification string (`\r` and `\n` excluded).
+    pub v_s: arch::Bytes<'b>,
+
+    /// Payload of the client's `SSH_MSG_KEXINIT` message.
+    pub i_c: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Payload of the server's `SSH_MSG_KEXINIT` message.
+    pub i_s: Lengthed<&'b trans::KexInit<'b>>,
+
+    /// Server's public host key.
+    pub k_s: arch::Bytes<'b>,
+
+    /// Client's KEM public key and ECDH public key.
+    pub c_init: arch::Bytes<'b>,
+
+    /// Server's KEM ciphertext and ECDH public key.
+    pub s_reply: arch::Bytes<'b>,
+
+    /// Computed shared secret, encoded as a string rather than a `mpint`.
+    pub k: arch::Bytes<'b>,
+}
+
+impl Hybrid<'_> {
+    /// Produce the exchange hash with the specified digest algorithm.
+    pub fn hash<D: digest::Digest>(&self) -> digest::Output<D> {
+        hash::<D>(self)
+    }
+}
+
+fn hash<D: digest::Digest>(
+    value: &impl for<'a> binrw::BinWrite<Args<'a> = ()>,
+) -> digest::Output<D> {
+    let mut buffer = Vec::new();
+    value
+        .write_be(&mut std::io::Cursor::new(&mut buffer))
+        .expect("The binrw structure serialization failed");
+
+    D::digest(&buffer)
+}
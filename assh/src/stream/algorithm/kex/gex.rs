@@ -0,0 +1,248 @@
+use secrecy::{ExposeSecret, SecretBox};
+use signature::SignatureEncoding;
+use signature::digest::{Digest, FixedOutputReset};
+use ssh_packet::{
+    arch::{self, MpInt},
+    binrw::{self, binrw},
+};
+
+use super::{
+    KexMeta,
+    dh::Ephemeral,
+    exchange,
+    moduli::{MAX_BITS, MIN_BITS, Moduli},
+};
+use crate::{
+    Error, Pipe, Result,
+    stream::{
+        Stream, Transport,
+        algorithm::key::{self, HostKey, PeerHostKey},
+    },
+};
+
+/// The `SSH_MSG_KEX_DH_GEX_REQUEST` message.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4419#section-3>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 34_u8)]
+pub struct KexDhGexRequest {
+    /// Minimal size in bits of an acceptable group.
+    pub min: u32,
+
+    /// Preferred size in bits of the group the server will send.
+    pub n: u32,
+
+    /// Maximal size in bits of an acceptable group.
+    pub max: u32,
+}
+
+/// The `SSH_MSG_KEX_DH_GEX_GROUP` message.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4419#section-3>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 31_u8)]
+pub struct KexDhGexGroup<'b> {
+    /// Safe prime of the group.
+    pub p: arch::MpInt<'b>,
+
+    /// Generator of the group.
+    pub g: arch::MpInt<'b>,
+}
+
+/// The `SSH_MSG_KEX_DH_GEX_INIT` message.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4419#section-3>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 32_u8)]
+pub struct KexDhGexInit<'b> {
+    /// Exchange value sent by the client.
+    pub e: arch::MpInt<'b>,
+}
+
+/// The `SSH_MSG_KEX_DH_GEX_REPLY` message.
+///
+/// see <https://datatracker.ietf.org/doc/html/rfc4419#section-3>.
+#[binrw]
+#[derive(Debug, Clone)]
+#[brw(big, magic = 33_u8)]
+pub struct KexDhGexReply<'b> {
+    /// Server's public host key.
+    pub k_s: arch::Bytes<'b>,
+
+    /// Exchange value sent by the server.
+    pub f: arch::MpInt<'b>,
+
+    /// Signature of the exchange hash.
+    pub signature: arch::Bytes<'b>,
+}
+
+/// Estimate the group size providing the same security level than the digest,
+/// as per the NIST SP 800-57 recommendations.
+fn preferred<H: Digest>() -> u32 {
+    match <H as Digest>::output_size() * 8 / 2 {
+        ..=112 => MIN_BITS,
+        113..=128 => 3072,
+        129..=192 => 7680,
+        _ => MAX_BITS,
+    }
+}
+
+/// Compute the size in bits of the value of an [`MpInt`].
+fn bits(value: &MpInt) -> u32 {
+    let value = value.as_ref();
+    let leading = value.iter().take_while(|byte| **byte == 0).count();
+
+    match value.get(leading) {
+        Some(byte) => (value.len() - leading) as u32 * 8 - byte.leading_zeros(),
+        None => 0,
+    }
+}
+
+pub async fn as_client<H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+) -> Result<(Transport, PeerHostKey)> {
+    let request = KexDhGexRequest {
+        min: MIN_BITS,
+        n: preferred::<H>(),
+        max: MAX_BITS,
+    };
+    stream.send(&request).await?;
+
+    let group: KexDhGexGroup = stream.recv().await?.to()?;
+    if !(request.min..=request.max).contains(&bits(&group.p)) {
+        return Err(Error::KexError);
+    }
+
+    let ephemeral = Ephemeral::new::<H>(
+        crypto_bigint::BoxedUint::from_be_slice_vartime(group.p.as_ref()),
+        group.g.as_ref(),
+    )?;
+    let e = ephemeral.public();
+
+    stream
+        .send(&KexDhGexInit {
+            e: MpInt::positive(&e),
+        })
+        .await?;
+
+    let dh: KexDhGexReply = stream.recv().await?.to()?;
+
+    let secret = ephemeral.shared(&dh.f)?;
+    let secret = SecretBox::new(MpInt::positive(secret.expose_secret()).into());
+
+    let k_s = PeerHostKey::decode(&dh.k_s)?;
+    let hash = exchange::DhGex {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: dh.k_s,
+        min: request.min,
+        n: request.n,
+        max: request.max,
+        p: group.p,
+        g: group.g,
+        e: MpInt::positive(&e),
+        f: dh.f,
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    k_s.verify(
+        &key::negociate(client.kexinit, server.kexinit)?,
+        &hash,
+        dh.signature.as_ref(),
+    )?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok((
+        Transport::as_client::<H>(
+            client,
+            server,
+            secret.expose_secret().as_ref(),
+            &hash,
+            session_id,
+        ),
+        k_s,
+    ))
+}
+
+pub async fn as_server<H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+    key: HostKey<'_>,
+    moduli: &dyn Moduli,
+) -> Result<Transport> {
+    let request: KexDhGexRequest = stream.recv().await?.to()?;
+
+    let min = request.min.max(MIN_BITS);
+    let max = request.max.min(MAX_BITS);
+    let n = request.n.clamp(MIN_BITS, MAX_BITS);
+    if max < min || n < min || max < n {
+        return Err(Error::KexError);
+    }
+
+    let modulus = moduli.select(min, n, max).ok_or(Error::KexError)?;
+    let group = KexDhGexGroup {
+        p: MpInt::positive(&modulus.prime),
+        g: MpInt::positive(&modulus.generator),
+    };
+    stream.send(&group).await?;
+
+    let dh: KexDhGexInit = stream.recv().await?.to()?;
+
+    let ephemeral = Ephemeral::new::<H>(
+        crypto_bigint::BoxedUint::from_be_slice_vartime(&modulus.prime),
+        &modulus.generator,
+    )?;
+    let f = ephemeral.public();
+
+    let secret = ephemeral.shared(&dh.e)?;
+    let secret = SecretBox::new(MpInt::positive(secret.expose_secret()).into());
+
+    let k_s = key.blob()?;
+
+    let hash = exchange::DhGex {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: k_s.as_slice().into(),
+        min: request.min,
+        n: request.n,
+        max: request.max,
+        p: group.p,
+        g: group.g,
+        e: dh.e,
+        f: MpInt::positive(&f),
+        k: secret.expose_secret().as_borrow(),
+    }
+    .hash::<H>();
+
+    let signature = key.sign(&hash).await?;
+
+    stream
+        .send(&KexDhGexReply {
+            k_s: k_s.into(),
+            f: MpInt::positive(&f),
+            signature: signature.to_vec().into(),
+        })
+        .await?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok(Transport::as_server::<H>(
+        server,
+        client,
+        secret.expose_secret().as_ref(),
+        &hash,
+        session_id,
+    ))
+}
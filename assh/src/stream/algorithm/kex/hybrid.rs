@@ -0,0 +1,155 @@
+use secrecy::{ExposeSecret, SecretSlice};
+use signature::SignatureEncoding;
+use signature::digest::{Digest, FixedOutputReset};
+use ssh_packet::trans::{KexEcdhInit, KexEcdhReply};
+
+use super::{
+    KexMeta,
+    ecdh::{Curve, X25519},
+    exchange,
+};
+use crate::{
+    Error, Pipe, Result,
+    stream::{
+        Stream, Transport,
+        algorithm::key::{self, HostKey, PeerHostKey},
+    },
+};
+
+/// A key-encapsulation mechanism usable in the hybrid key-exchange.
+pub trait Kem {
+    /// The decapsulation key of our side of the exchange.
+    type Secret;
+
+    /// Size of the encoded encapsulation key.
+    const PUBLIC_KEY_SIZE: usize;
+
+    /// Size of the encoded ciphertext.
+    const CIPHERTEXT_SIZE: usize;
+
+    /// Generate an ephemeral decapsulation key along with it's encoded encapsulation key.
+    fn generate() -> (Self::Secret, Vec<u8>);
+
+    /// Encapsulate a shared secret to the peer's encoded encapsulation key, producing the ciphertext.
+    fn encapsulate(public: &[u8]) -> Result<(Vec<u8>, SecretSlice<u8>)>;
+
+    /// Decapsulate the shared secret from the peer's ciphertext.
+    fn decapsulate(secret: Self::Secret, ciphertext: &[u8]) -> Result<SecretSlice<u8>>;
+}
+
+/// Combine the KEM and ECDH shared secrets into the exchange's shared secret.
+fn combine<H: Digest>(kem: &SecretSlice<u8>, ecdh: &SecretSlice<u8>) -> SecretSlice<u8> {
+    H::new()
+        .chain_update(kem.expose_secret())
+        .chain_update(ecdh.expose_secret())
+        .finalize()
+        .to_vec()
+        .into()
+}
+
+pub async fn as_client<K: Kem, H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+) -> Result<(Transport, PeerHostKey)> {
+    let (kem_c, kem_pk) = K::generate();
+    let (e_c, q_c) = X25519::generate();
+
+    let c_init = [kem_pk, q_c].concat();
+
+    stream
+        .send(&KexEcdhInit {
+            q_c: c_init.as_slice().into(),
+        })
+        .await?;
+
+    let ecdh: KexEcdhReply = stream.recv().await?.to()?;
+    if ecdh.q_s.len() != K::CIPHERTEXT_SIZE + 32 {
+        return Err(Error::KexError);
+    }
+
+    let (ciphertext, q_s) = ecdh.q_s.split_at(K::CIPHERTEXT_SIZE);
+    let secret = combine::<H>(
+        &K::decapsulate(kem_c, ciphertext)?,
+        &X25519::shared(e_c, q_s)?,
+    );
+
+    let k_s = PeerHostKey::decode(&ecdh.k_s)?;
+    let hash = exchange::Hybrid {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: ecdh.k_s,
+        c_init: c_init.as_slice().into(),
+        s_reply: ecdh.q_s.as_ref().into(),
+        k: secret.expose_secret().into(),
+    }
+    .hash::<H>();
+
+    k_s.verify(
+        &key::negociate(client.kexinit, server.kexinit)?,
+        &hash,
+        ecdh.signature.as_ref(),
+    )?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok((
+        Transport::as_client::<H>(client, server, secret.expose_secret(), &hash, session_id),
+        k_s,
+    ))
+}
+
+pub async fn as_server<K: Kem, H: Digest + FixedOutputReset>(
+    stream: &mut Stream<impl Pipe>,
+    client: KexMeta<'_>,
+    server: KexMeta<'_>,
+    key: HostKey<'_>,
+) -> Result<Transport> {
+    let ecdh: KexEcdhInit = stream.recv().await?.to()?;
+    if ecdh.q_c.len() != K::PUBLIC_KEY_SIZE + 32 {
+        return Err(Error::KexError);
+    }
+
+    let (kem_pk, q_c) = ecdh.q_c.split_at(K::PUBLIC_KEY_SIZE);
+    let (ciphertext, kem_secret) = K::encapsulate(kem_pk)?;
+    let (e_s, q_s) = X25519::generate();
+
+    let secret = combine::<H>(&kem_secret, &X25519::shared(e_s, q_c)?);
+    let s_reply = [ciphertext, q_s].concat();
+
+    let k_s = key.blob()?;
+
+    let hash = exchange::Hybrid {
+        v_c: client.id.to_string().into_bytes().into(),
+        v_s: server.id.to_string().into_bytes().into(),
+        i_c: (client.kexinit).into(),
+        i_s: (server.kexinit).into(),
+        k_s: k_s.as_slice().into(),
+        c_init: ecdh.q_c.as_ref().into(),
+        s_reply: s_reply.as_slice().into(),
+        k: secret.expose_secret().into(),
+    }
+    .hash::<H>();
+
+    let signature = key.sign(&hash).await?;
+
+    stream
+        .send(&KexEcdhReply {
+            k_s: k_s.into(),
+            q_s: s_reply.into(),
+            signature: signature.to_vec().into(),
+        })
+        .await?;
+
+    let session_id = stream.with_session(&hash);
+
+    Ok(Transport::as_server::<H>(
+        server,
+        client,
+        secret.expose_secret(),
+        &hash,
+        session_id,
+    ))
+}
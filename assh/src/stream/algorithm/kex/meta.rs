@@ -29,11 +29,20 @@ impl<'k> KexMeta<'k> {
         Cipher: Negociate<S>,
         Hmac: Negociate<S>,
     {
+        let cipher = <Cipher as Negociate<S>>::negociate(clientkex, serverkex)?;
+
+        // The HMAC negociation is skipped when the cipher provides authentication.
+        let hmac = if cipher.authenticated() {
+            Hmac::None
+        } else {
+            <Hmac as Negociate<S>>::negociate(clientkex, serverkex)?
+        };
+
         Ok(Self {
             id,
             compress: <Compress as Negociate<S>>::negociate(clientkex, serverkex)?,
-            cipher: <Cipher as Negociate<S>>::negociate(clientkex, serverkex)?,
-            hmac: <Hmac as Negociate<S>>::negociate(clientkex, serverkex)?,
+            cipher,
+            hmac,
             kexinit: if TypeId::of::<S>() == TypeId::of::<Client>() {
                 clientkex
             } else if TypeId::of::<S>() == TypeId::of::<Server>() {
@@ -0,0 +1,172 @@
+//! The _ML-KEM-768_ key-encapsulation mechanism.
+//!
+//! see <https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf>.
+
+use ml_kem::{
+    Decapsulate, Encapsulate, KeyExport, KeySizeUser, TryKeyInit, array::typenum::Unsigned,
+    ml_kem_768,
+};
+use secrecy::SecretSlice;
+use zeroize::Zeroize;
+
+use super::hybrid::Kem;
+use crate::{Error, Result};
+
+/// The _ML-KEM-768_ key-encapsulation mechanism.
+pub struct MlKem768;
+
+impl Kem for MlKem768 {
+    type Secret = ml_kem_768::DecapsulationKey;
+
+    const PUBLIC_KEY_SIZE: usize = <ml_kem_768::EncapsulationKey as KeySizeUser>::KeySize::USIZE;
+    const CIPHERTEXT_SIZE: usize = <ml_kem::MlKem768 as ml_kem::Kem>::CiphertextSize::USIZE;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        let (secret, public) =
+            <ml_kem::MlKem768 as ml_kem::Kem>::generate_keypair_from_rng(&mut rand::rng());
+
+        (secret, public.to_bytes().to_vec())
+    }
+
+    fn encapsulate(public: &[u8]) -> Result<(Vec<u8>, SecretSlice<u8>)> {
+        let public =
+            ml_kem_768::EncapsulationKey::new_from_slice(public).map_err(|_| Error::KexError)?;
+
+        let (ciphertext, mut shared) = public.encapsulate_with_rng(&mut rand::rng());
+        let secret = shared.to_vec().into();
+        shared.zeroize();
+
+        Ok((ciphertext.to_vec(), secret))
+    }
+
+    fn decapsulate(secret: Self::Secret, ciphertext: &[u8]) -> Result<SecretSlice<u8>> {
+        // The decapsulation is constant-time, and implicitly rejects invalid ciphertexts
+        // by resolving to a pseudo-random secret, failing the exchange's signature verification.
+        let mut shared = secret
+            .decapsulate_slice(ciphertext)
+            .map_err(|_| Error::KexError)?;
+        let secret = shared.to_vec().into();
+        shared.zeroize();
+
+        Ok(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(
+        deprecated,
+        reason = "the ACVP vectors use the expanded decapsulation keys"
+    )]
+
+    use std::collections::HashMap;
+
+    use ml_kem::FromSeed;
+    use secrecy::ExposeSecret;
+    use sha2::Digest;
+
+    use super::*;
+
+    /// The test vectors, as groups of `name = hex` lines separated by blank lines.
+    const VECTORS: &str = include_str!("mlkem768.kat");
+
+    fn vectors() -> Vec<HashMap<&'static str, Vec<u8>>> {
+        VECTORS
+            .split("\n\n")
+            .map(|group| {
+                group
+                    .lines()
+                    .filter(|line| !line.starts_with('#'))
+                    .filter_map(|line| line.split_once(" = "))
+                    .map(|(name, value)| {
+                        let value = (0..value.len())
+                            .step_by(2)
+                            .map(|idx| {
+                                u8::from_str_radix(&value[idx..idx + 2], 16)
+                                    .expect("the vectors are hex")
+                            })
+                            .collect();
+
+                        (name, value)
+                    })
+                    .collect()
+            })
+            .filter(|vector: &HashMap<_, _>| !vector.is_empty())
+            .collect()
+    }
+
+    fn secret(dk: &[u8]) -> ml_kem_768::DecapsulationKey {
+        ml_kem_768::DecapsulationKey::from_expanded(
+            dk.try_into().expect("the decapsulation key is 2400 bytes"),
+        )
+        .expect("the decapsulation key is valid")
+    }
+
+    #[test]
+    fn it_derives_the_encapsulation_key_from_seed() {
+        let seed = std::array::from_fn::<u8, 64, _>(|i| i as u8);
+
+        let (_, public) = ml_kem::MlKem768::from_seed(&seed.into());
+
+        assert_eq!(
+            sha2::Sha256::digest(public.to_bytes())[..],
+            [
+                0x0b, 0x79, 0x34, 0xc8, 0x31, 0x25, 0xc7, 0x88, 0x99, 0x5e, 0x2b, 0xa6, 0xbd, 0x76,
+                0x1e, 0x33, 0x04, 0x6b, 0x3e, 0x40, 0x57, 0x1b, 0xe5, 0x3e, 0x02, 0x33, 0x09, 0xa2,
+                0x9f, 0x39, 0x8c, 0xc9
+            ]
+        );
+    }
+
+    #[test]
+    fn it_encapsulates_the_known_answers() {
+        for vector in vectors().iter().filter(|vector| vector.contains_key("m")) {
+            let public = ml_kem_768::EncapsulationKey::new_from_slice(&vector["ek"])
+                .expect("the encapsulation key is valid");
+            let (ciphertext, shared) = public.encapsulate_deterministic(
+                vector["m"]
+                    .as_slice()
+                    .try_into()
+                    .expect("the message is 32 bytes"),
+            );
+
+            assert_eq!(ciphertext.as_slice(), vector["c"]);
+            assert_eq!(shared.as_slice(), vector["k"]);
+
+            assert_eq!(
+                MlKem768::decapsulate(secret(&vector["dk"]), &vector["c"])
+                    .expect("the ciphertext is valid")
+                    .expose_secret(),
+                vector["k"]
+            );
+        }
+    }
+
+    #[test]
+    fn it_decapsulates_the_known_answers() {
+        // The vectors include a modified ciphertext, implicitly rejected with a pseudo-random secret.
+        for vector in vectors().iter().filter(|vector| !vector.contains_key("m")) {
+            assert_eq!(
+                MlKem768::decapsulate(secret(&vector["dk"]), &vector["c"])
+                    .expect("the ciphertext is well-sized")
+                    .expose_secret(),
+                vector["k"]
+            );
+        }
+    }
+
+    #[test]
+    fn it_decapsulates_the_encapsulated_secret() {
+        let (secret, public) = MlKem768::generate();
+
+        let (ciphertext, shared) =
+            MlKem768::encapsulate(&public).expect("the encapsulation key is valid");
+
+        assert_eq!(
+            MlKem768::decapsulate(secret, &ciphertext)
+                .expect("the ciphertext is valid")
+                .expose_secret(),
+            shared.expose_secret()
+        );
+    }
+}
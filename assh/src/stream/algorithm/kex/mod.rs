@@ -1,19 +1,57 @@
-use ssh_key::PrivateKey;
 use ssh_packet::{arch::NameList, trans::KexInit};
-use strum::{AsRefStr, EnumString};
+use strum::{AsRefStr, EnumIter, EnumString};
 
-use super::Negociate;
+use super::{
+    Negociate,
+    key::{HostKey, PeerHostKey},
+};
 use crate::{
     Error, Pipe, Result,
     stream::{Stream, Transport},
 };
 
-// TODO: (reliability) Investigate the randomly-occuring `invalid signature` occuring against OpenSSH.
-
 mod meta;
 pub use meta::KexMeta;
 
-mod curve25519;
+mod exchange;
+
+pub mod moduli;
+use moduli::Moduli;
+
+mod dh;
+mod ecdh;
+mod gex;
+mod hybrid;
+mod mlkem;
+mod sntrup;
+
+/// The _client_'s marker for extension negotiation support, as per RFC 8308.
+pub const EXT_INFO_C: &str = "ext-info-c";
+
+/// The _server_'s marker for extension negotiation support, as per RFC 8308.
+pub const EXT_INFO_S: &str = "ext-info-s";
+
+/// The _client_'s marker for strict key-exchange support, as per OpenSSH's `PROTOCOL`.
+pub const KEX_STRICT_C: &str = "kex-strict-c-v00@openssh.com";
+
+/// The _server_'s marker for strict key-exchange support, as per OpenSSH's `PROTOCOL`.
+pub const KEX_STRICT_S: &str = "kex-strict-s-v00@openssh.com";
+
+/// Whether the `name` is a pseudo-algorithm signaling a capability,
+/// which is advertised in the `kex_algorithms` but never to be negociated.
+fn is_marker(name: &str) -> bool {
+    [EXT_INFO_C, EXT_INFO_S, KEX_STRICT_C, KEX_STRICT_S].contains(&name)
+}
+
+/// Whether the peer's guess of the negociated algorithms was right, as per RFC 4253 section 7,
+/// meaning both sides prefer the same _key-exchange_ and _host key_ algorithms.
+pub fn guessed(kexinit: &KexInit, peerkexinit: &KexInit) -> bool {
+    let first = |list: &NameList| list.into_iter().next().map(|name| name.to_string());
+
+    first(&kexinit.kex_algorithms) == first(&peerkexinit.kex_algorithms)
+        && first(&kexinit.server_host_key_algorithms)
+            == first(&peerkexinit.server_host_key_algorithms)
+}
 
 impl Negociate for Kex {
     const ERR: Error = Error::NoCommonKex;
@@ -21,27 +59,81 @@ impl Negociate for Kex {
     fn field<'f>(kex: &'f KexInit) -> &'f NameList<'f> {
         &kex.kex_algorithms
     }
-}
 
-// TODO: (feature) Implement the following legacy key-exchange methods (`diffie-hellman-group14-sha256`, `diffie-hellman-group14-sha1`, `diffie-hellman-group1-sha1`).
+    fn negociate(clientkex: &KexInit, serverkex: &KexInit) -> Result<Self> {
+        Self::field(clientkex)
+            .into_iter()
+            .filter(|name| !is_marker(name))
+            .find(|name| {
+                Self::field(serverkex)
+                    .into_iter()
+                    .any(|other| name == &other)
+            })
+            .ok_or(Self::ERR)?
+            .parse()
+            .map_err(|_| Self::ERR)
+    }
+}
 
 /// SSH key-exchange algorithms.
 #[non_exhaustive]
-#[derive(Debug, Clone, PartialEq, EnumString, AsRefStr)]
+#[derive(Debug, Clone, PartialEq, EnumString, AsRefStr, EnumIter)]
 #[strum(serialize_all = "kebab-case")]
 pub enum Kex {
+    /// ML-KEM-768 hybridized with Curve25519 ECDH, with sha-2-256 digest.
+    #[strum(serialize = "mlkem768x25519-sha256")]
+    Mlkem768X25519Sha256,
+
+    /// Streamlined NTRU Prime 761 hybridized with Curve25519 ECDH, with sha-2-512 digest.
+    #[strum(serialize = "sntrup761x25519-sha512")]
+    Sntrup761X25519Sha512,
+
+    /// Streamlined NTRU Prime 761 hybridized with Curve25519 ECDH, with sha-2-512 digest (pre-standardization).
+    #[strum(serialize = "sntrup761x25519-sha512@openssh.com")]
+    Sntrup761X25519Sha512Openssh,
+
     /// Curve25519 ECDH with sha-2-256 digest.
     Curve25519Sha256,
 
     /// Curve25519 ECDH with sha-2-256 digest (pre-RFC 8731).
     #[strum(serialize = "curve25519-sha256@libssh.org")]
     Curve25519Sha256Libssh,
-    //
-    // DiffieHellmanGroup14Sha256,
-    //
-    // DiffieHellmanGroup14Sha1,
-    //
-    // DiffieHellmanGroup1Sha1,
+
+    /// NIST P-256 ECDH with sha-2-256 digest.
+    EcdhSha2Nistp256,
+
+    /// NIST P-384 ECDH with sha-2-384 digest.
+    EcdhSha2Nistp384,
+
+    /// NIST P-521 ECDH with sha-2-512 digest.
+    EcdhSha2Nistp521,
+
+    /// Diffie-Hellman group-exchange with sha-2-256 digest.
+    DiffieHellmanGroupExchangeSha256,
+
+    /// Diffie-Hellman group-exchange with sha-1 digest.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+    DiffieHellmanGroupExchangeSha1,
+
+    /// Diffie-Hellman with the 4096-bit MODP group and sha-2-512 digest.
+    DiffieHellmanGroup16Sha512,
+
+    /// Diffie-Hellman with the 8192-bit MODP group and sha-2-512 digest.
+    DiffieHellmanGroup18Sha512,
+
+    /// Diffie-Hellman with the 2048-bit MODP group and sha-2-256 digest.
+    DiffieHellmanGroup14Sha256,
+
+    /// Diffie-Hellman with the 2048-bit MODP group and sha-1 digest.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+    DiffieHellmanGroup14Sha1,
+
+    /// Diffie-Hellman with the 1024-bit Oakley group and sha-1 digest.
+    #[cfg(feature = "insecure")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "insecure")))]
+    DiffieHellmanGroup1Sha1,
 }
 
 impl Kex {
@@ -50,10 +142,49 @@ impl Kex {
         stream: &mut Stream<impl Pipe>,
         client: KexMeta<'_>,
         server: KexMeta<'_>,
-    ) -> Result<Transport> {
+    ) -> Result<(Transport, PeerHostKey)> {
         match self {
+            Self::Mlkem768X25519Sha256 => {
+                hybrid::as_client::<mlkem::MlKem768, sha2::Sha256>(stream, client, server).await
+            }
+            Self::Sntrup761X25519Sha512 | Self::Sntrup761X25519Sha512Openssh => {
+                hybrid::as_client::<sntrup::Sntrup761, sha2::Sha512>(stream, client, server).await
+            }
             Self::Curve25519Sha256 | Self::Curve25519Sha256Libssh => {
-                curve25519::as_client::<sha2::Sha256>(stream, client, server).await
+                ecdh::as_client::<ecdh::X25519, sha2::Sha256>(stream, client, server).await
+            }
+            Self::EcdhSha2Nistp256 => {
+                ecdh::as_client::<p256::NistP256, sha2::Sha256>(stream, client, server).await
+            }
+            Self::EcdhSha2Nistp384 => {
+                ecdh::as_client::<p384::NistP384, sha2::Sha384>(stream, client, server).await
+            }
+            Self::EcdhSha2Nistp521 => {
+                ecdh::as_client::<p521::NistP521, sha2::Sha512>(stream, client, server).await
+            }
+            Self::DiffieHellmanGroup18Sha512 => {
+                dh::as_client::<sha2::Sha512>(stream, client, server, &dh::GROUP18).await
+            }
+            Self::DiffieHellmanGroupExchangeSha256 => {
+                gex::as_client::<sha2::Sha256>(stream, client, server).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroupExchangeSha1 => {
+                gex::as_client::<sha1::Sha1>(stream, client, server).await
+            }
+            Self::DiffieHellmanGroup16Sha512 => {
+                dh::as_client::<sha2::Sha512>(stream, client, server, &dh::GROUP16).await
+            }
+            Self::DiffieHellmanGroup14Sha256 => {
+                dh::as_client::<sha2::Sha256>(stream, client, server, &dh::GROUP14).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroup14Sha1 => {
+                dh::as_client::<sha1::Sha1>(stream, client, server, &dh::GROUP14).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroup1Sha1 => {
+                dh::as_client::<sha1::Sha1>(stream, client, server, &dh::GROUP1).await
             }
         }
     }
@@ -63,11 +194,53 @@ impl Kex {
         stream: &mut Stream<impl Pipe>,
         client: KexMeta<'_>,
         server: KexMeta<'_>,
-        key: &PrivateKey,
+        key: HostKey<'_>,
+        moduli: &dyn Moduli,
     ) -> Result<Transport> {
         match self {
+            Self::Mlkem768X25519Sha256 => {
+                hybrid::as_server::<mlkem::MlKem768, sha2::Sha256>(stream, client, server, key)
+                    .await
+            }
+            Self::Sntrup761X25519Sha512 | Self::Sntrup761X25519Sha512Openssh => {
+                hybrid::as_server::<sntrup::Sntrup761, sha2::Sha512>(stream, client, server, key)
+                    .await
+            }
             Self::Curve25519Sha256 | Self::Curve25519Sha256Libssh => {
-                curve25519::as_server::<sha2::Sha256>(stream, client, server, key).await
+                ecdh::as_server::<ecdh::X25519, sha2::Sha256>(stream, client, server, key).await
+            }
+            Self::EcdhSha2Nistp256 => {
+                ecdh::as_server::<p256::NistP256, sha2::Sha256>(stream, client, server, key).await
+            }
+            Self::EcdhSha2Nistp384 => {
+                ecdh::as_server::<p384::NistP384, sha2::Sha384>(stream, client, server, key).await
+            }
+            Self::EcdhSha2Nistp521 => {
+                ecdh::as_server::<p521::NistP521, sha2::Sha512>(stream, client, server, key).await
+            }
+            Self::DiffieHellmanGroup18Sha512 => {
+                dh::as_server::<sha2::Sha512>(stream, client, server, key, &dh::GROUP18).await
+            }
+            Self::DiffieHellmanGroupExchangeSha256 => {
+                gex::as_server::<sha2::Sha256>(stream, client, server, key, moduli).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroupExchangeSha1 => {
+                gex::as_server::<sha1::Sha1>(stream, client, server, key, moduli).await
+            }
+            Self::DiffieHellmanGroup16Sha512 => {
+                dh::as_server::<sha2::Sha512>(stream, client, server, key, &dh::GROUP16).await
+            }
+            Self::DiffieHellmanGroup14Sha256 => {
+                dh::as_server::<sha2::Sha256>(stream, client, server, key, &dh::GROUP14).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroup14Sha1 => {
+                dh::as_server::<sha1::Sha1>(stream, client, server, key, &dh::GROUP14).await
+            }
+            #[cfg(feature = "insecure")]
+            Self::DiffieHellmanGroup1Sha1 => {
+                dh::as_server::<sha1::Sha1>(stream, client, server, key, &dh::GROUP1).await
             }
         }
     }
@@ -0,0 +1,233 @@
+//! Sources of groups for the _Diffie-Hellman group-exchange_ key-exchanges.
+
+use std::{path::Path, sync::OnceLock};
+
+use rand::seq::IndexedRandom;
+
+use super::dh;
+
+/// The smallest group size accepted in the group-exchange, as recommended by the RFC 8270.
+pub(crate) const MIN_BITS: u32 = 2048;
+
+/// The largest group size accepted in the group-exchange.
+pub(crate) const MAX_BITS: u32 = 8192;
+
+/// A safe-prime group for the _Diffie-Hellman group-exchange_.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modulus {
+    /// The size in bits of the `prime`.
+    pub bits: u32,
+
+    /// The generator of the group, `g`, as big-endian bytes.
+    pub generator: Vec<u8>,
+
+    /// The safe-prime defining the group, `p`, as big-endian bytes.
+    pub prime: Vec<u8>,
+}
+
+impl From<&dh::Group> for Modulus {
+    fn from(group: &dh::Group) -> Self {
+        Self {
+            bits: group.bits(),
+            generator: vec![2],
+            prime: group.prime().to_be_bytes().into(),
+        }
+    }
+}
+
+/// A _server_-side provider of groups for the _Diffie-Hellman group-exchange_.
+pub trait Moduli: std::fmt::Debug + Send + Sync {
+    /// Select a group of `min` to `max` bits, as close as possible to the `preferred` size,
+    /// or `None` if no group is suitable for the request.
+    fn select(&self, min: u32, preferred: u32, max: u32) -> Option<Modulus>;
+}
+
+/// A [`Moduli`] provider backed by the OpenSSH `moduli(5)` file format,
+/// which falls back to the fixed MODP groups when no group in the file fits the request.
+#[derive(Debug, Clone, Default)]
+pub struct ModuliFile {
+    moduli: Vec<Modulus>,
+}
+
+impl ModuliFile {
+    /// The default location of the system's moduli file.
+    pub const PATH: &str = "/etc/ssh/moduli";
+
+    /// Read and parse the moduli file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Parse the moduli from the `moduli(5)` formatted `contents`,
+    /// skipping comments, malformed lines and groups that are not safe-primes.
+    pub fn parse(contents: &str) -> Self {
+        const MODULI_TYPE_SAFE: u32 = 2;
+        const MODULI_TESTS_COMPOSITE: u32 = 0x01;
+
+        let moduli = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace().skip(1);
+
+                let kind: u32 = fields.next()?.parse().ok()?;
+                let tests: u32 = fields.next()?.parse().ok()?;
+                let _trials = fields.next()?;
+                let size: u32 = fields.next()?.parse().ok()?;
+                let generator: u32 = fields.next()?.parse().ok()?;
+                let prime = hex(fields.next()?)?;
+
+                if kind != MODULI_TYPE_SAFE || tests & MODULI_TESTS_COMPOSITE != 0 {
+                    return None;
+                }
+
+                // The size field is the size of the prime minus one bit.
+                let bits = size + 1;
+                if bits != prime.len() as u32 * 8 - prime[0].leading_zeros() {
+                    return None;
+                }
+
+                Some(Modulus {
+                    bits,
+                    generator: generator
+                        .to_be_bytes()
+                        .into_iter()
+                        .skip_while(|byte| *byte == 0)
+                        .collect(),
+                    prime,
+                })
+            })
+            .collect();
+
+        Self { moduli }
+    }
+
+    /// Access the parsed moduli.
+    pub fn moduli(&self) -> &[Modulus] {
+        &self.moduli
+    }
+}
+
+impl Moduli for ModuliFile {
+    fn select(&self, min: u32, preferred: u32, max: u32) -> Option<Modulus> {
+        // Pick the smallest size above the preferred size, or the largest below it,
+        // mimicking the OpenSSH selection algorithm.
+        let best = self
+            .moduli
+            .iter()
+            .map(|modulus| modulus.bits)
+            .filter(|bits| (min..=max).contains(bits))
+            .reduce(|best, bits| {
+                if (bits > preferred && bits < best) || (bits > best && best < preferred) {
+                    bits
+                } else {
+                    best
+                }
+            });
+
+        match best {
+            Some(best) => self
+                .moduli
+                .iter()
+                .filter(|modulus| modulus.bits == best)
+                .collect::<Vec<_>>()
+                .choose(&mut rand::rng())
+                .map(|modulus| (*modulus).clone()),
+            None => [dh::GROUP14, dh::GROUP16, dh::GROUP18]
+                .iter()
+                .filter(|group| (min..=max).contains(&group.bits()))
+                .find(|group| group.bits() >= preferred)
+                .or_else(|| {
+                    [dh::GROUP18, dh::GROUP16, dh::GROUP14]
+                        .iter()
+                        .find(|group| (min..=max).contains(&group.bits()))
+                })
+                .map(Modulus::from),
+        }
+    }
+}
+
+/// A [`Moduli`] provider reading the system's moduli file at [`ModuliFile::PATH`]
+/// upon the first group-exchange, and falling back to the fixed MODP groups if it is unreadable.
+#[derive(Debug, Default)]
+pub struct SystemModuli {
+    file: OnceLock<ModuliFile>,
+}
+
+impl Moduli for SystemModuli {
+    fn select(&self, min: u32, preferred: u32, max: u32) -> Option<Modulus> {
+        self.file
+            .get_or_init(|| ModuliFile::open(ModuliFile::PATH).unwrap_or_default())
+            .select(min, preferred, max)
+    }
+}
+
+fn hex(value: &str) -> Option<Vec<u8>> {
+    if value.is_empty() || !value.is_ascii() {
+        return None;
+    }
+
+    let value = if !value.len().is_multiple_of(2) {
+        format!("0{value}")
+    } else {
+        value.to_string()
+    };
+
+    (0..value.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&value[idx..idx + 2], 16).ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|bytes| bytes[0] != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULI: &str = "\
+# Time Type Tests Tries Size Generator Modulus
+20230401000000 2 6 100 2047 2 FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF
+20230401000000 2 1 100 2047 5 FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF
+20230401000000 4 6 100 2047 2 FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF
+20230401000000 2 6 100 3071 2 malformed
+";
+
+    #[test]
+    fn it_parses_safe_primes_only() {
+        let moduli = ModuliFile::parse(MODULI);
+
+        assert_eq!(moduli.moduli().len(), 1);
+        assert_eq!(moduli.moduli()[0], Modulus::from(&dh::GROUP14));
+    }
+
+    #[test]
+    fn it_selects_within_range() {
+        let moduli = ModuliFile::parse(MODULI);
+
+        assert_eq!(
+            moduli.select(MIN_BITS, 4096, MAX_BITS),
+            Some(Modulus::from(&dh::GROUP14))
+        );
+        assert_eq!(
+            moduli.select(3072, 4096, MAX_BITS).map(|m| m.bits),
+            Some(4096)
+        );
+        assert_eq!(moduli.select(3072, 8192, 8192).map(|m| m.bits), Some(8192));
+        assert_eq!(moduli.select(3072, 3072, 3072), None);
+    }
+
+    #[test]
+    fn it_defaults_to_the_modp_groups() {
+        let moduli = ModuliFile::default();
+
+        assert_eq!(
+            moduli.select(MIN_BITS, 4096, MAX_BITS),
+            Some(Modulus::from(&dh::GROUP16))
+        );
+        assert_eq!(
+            moduli.select(MIN_BITS, 8192, MAX_BITS),
+            Some(Modulus::from(&dh::GROUP18))
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! The _Streamlined NTRU Prime 761_ key-encapsulation mechanism.
+//!
+//! see <https://ntruprime.cr.yp.to/nist/ntruprime-20201007.pdf>.
+
+use secrecy::SecretSlice;
+
+use super::hybrid::Kem;
+use crate::{Error, Result};
+
+/// The _sntrup761_ key-encapsulation mechanism.
+pub struct Sntrup761;
+
+impl Kem for Sntrup761 {
+    type Secret = sntrup761::DecapsulationKey;
+
+    const PUBLIC_KEY_SIZE: usize = sntrup761::PUBLIC_KEY_SIZE;
+    const CIPHERTEXT_SIZE: usize = sntrup761::CIPHERTEXT_SIZE;
+
+    fn generate() -> (Self::Secret, Vec<u8>) {
+        let (public, secret) = sntrup761::generate_key(rand::rng());
+
+        (secret, public.as_ref().to_vec())
+    }
+
+    fn encapsulate(public: &[u8]) -> Result<(Vec<u8>, SecretSlice<u8>)> {
+        let public = sntrup761::EncapsulationKey::try_from(public).map_err(|_| Error::KexError)?;
+
+        let (ciphertext, shared) = public.encapsulate(rand::rng());
+
+        Ok((
+            ciphertext.as_ref().to_vec(),
+            shared.as_ref().to_vec().into(),
+        ))
+    }
+
+    fn decapsulate(secret: Self::Secret, ciphertext: &[u8]) -> Result<SecretSlice<u8>> {
+        let ciphertext =
+            sntrup761::Ciphertext::try_from(ciphertext).map_err(|_| Error::KexError)?;
+
+        // The decapsulation is constant-time, and implicitly rejects invalid ciphertexts
+        // by resolving to a pseudo-random secret, failing the exchange's signature verification.
+        Ok(secret.decapsulate(&ciphertext).as_ref().to_vec().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::ExposeSecret;
+
+    use super::*;
+
+    #[test]
+    fn it_decapsulates_the_encapsulated_secret() {
+        let (secret, public) = Sntrup761::generate();
+
+        let (ciphertext, shared) =
+            Sntrup761::encapsulate(&public).expect("the encapsulation key is valid");
+        assert_eq!(ciphertext.len(), Sntrup761::CIPHERTEXT_SIZE);
+
+        assert_eq!(
+            Sntrup761::decapsulate(secret, &ciphertext)
+                .expect("the ciphertext is valid")
+                .expose_secret(),
+            shared.expose_secret()
+        );
+    }
+
+    #[test]
+    fn it_implicitly_rejects_modified_ciphertexts() {
+        let (secret, public) = Sntrup761::generate();
+
+        let (mut ciphertext, shared) =
+            Sntrup761::encapsulate(&public).expect("the encapsulation key is valid");
+        ciphertext[0] ^= 0xff;
+
+        let rejected = Sntrup761::decapsulate(secret.clone(), &ciphertext)
+            .expect("the ciphertext is well-sized");
+        assert_ne!(rejected.expose_secret(), shared.expose_secret());
+        assert_eq!(
+            Sntrup761::decapsulate(secret, &ciphertext)
+                .expect("the ciphertext is well-sized")
+                .expose_secret(),
+            rejected.expose_secret()
+        );
+    }
+}
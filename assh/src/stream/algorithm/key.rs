@@ -1,14 +1,143 @@
+use signature::Verifier;
 pub use ssh_key::Algorithm as Key;
-use ssh_packet::{arch::NameList, trans::KexInit};
+use ssh_key::{Certificate, HashAlg, PublicKey, Signature};
+use ssh_packet::trans::KexInit;
 
-use crate::Error;
+use crate::{Error, Result, side::server::HostKeySigner};
 
-use super::Negociate;
+/// Negociate the _server host key_ algorithm, where an OpenSSH certificate type
+/// resolves to the algorithm of the certified key.
+pub(crate) fn negociate(clientkex: &KexInit, serverkex: &KexInit) -> Result<Key> {
+    let name = clientkex
+        .server_host_key_algorithms
+        .preferred_in(&serverkex.server_host_key_algorithms)
+        .ok_or(Error::NoCommonKey)?;
 
-impl Negociate for Key {
-    const ERR: Error = Error::NoCommonKey;
+    Key::new_certificate(&name)
+        .or_else(|_| Key::new(&name))
+        .map_err(|_| Error::NoCommonKey)
+}
+
+/// The _server host key_ algorithms usable with a key of the `algorithm`, in order of preference,
+/// where RSA keys are used with sha-2 signatures, as `rsa-sha2-512` and `rsa-sha2-256`.
+pub(crate) fn variants(algorithm: Key) -> Vec<Key> {
+    match algorithm {
+        Key::Rsa { .. } => vec![
+            Key::Rsa {
+                hash: Some(HashAlg::Sha512),
+            },
+            Key::Rsa {
+                hash: Some(HashAlg::Sha256),
+            },
+        ],
+        algorithm => vec![algorithm],
+    }
+}
+
+/// A _server host key_, presented to the _client_ either as is
+/// or as an OpenSSH certificate of it's public part.
+#[derive(Debug, Clone)]
+pub(crate) struct HostKey<'k> {
+    /// The signer of the exchange hash.
+    pub key: &'k dyn HostKeySigner,
+
+    /// The _server host key_ algorithm, selecting the signature hash for RSA keys.
+    pub algorithm: Key,
+
+    /// The certificate presented in place of the public key, if any.
+    pub certificate: Option<&'k Certificate>,
+}
+
+impl HostKey<'_> {
+    /// The name of the _server host key_ algorithm, as advertised in the `SSH_MSG_KEXINIT`.
+    pub fn name(&self) -> String {
+        match self.certificate {
+            Some(_) => self.algorithm.to_certificate_type(),
+            None => self.algorithm.to_string(),
+        }
+    }
+
+    /// Sign the exchange `hash` with the signature algorithm of the _server host key_ algorithm.
+    pub async fn sign(&self, hash: &[u8]) -> Result<Signature> {
+        Ok(self.key.sign(&self.algorithm, hash).await?)
+    }
+
+    /// The `K_S` blob of the key-exchange reply, which is the encoded certificate when presented.
+    pub fn blob(&self) -> Result<Vec<u8>> {
+        Ok(match self.certificate {
+            Some(certificate) => certificate.to_bytes()?,
+            None => self.key.public_key().to_bytes()?,
+        })
+    }
+}
+
+/// A _server host key_ as received by the _client_ in the `K_S` blob of the key-exchange reply.
+#[derive(Debug, Clone)]
+pub(crate) struct PeerHostKey {
+    /// The public key verifying the exchange hash, which is the certified one for certificates.
+    pub key: PublicKey,
+
+    /// The certificate presented in place of the public key, if any.
+    pub certificate: Option<Certificate>,
+}
+
+impl PeerHostKey {
+    /// Decode the `K_S` blob, either as an OpenSSH certificate or a plain public key.
+    pub fn decode(blob: &[u8]) -> Result<Self> {
+        Ok(match Certificate::from_bytes(blob) {
+            Ok(certificate) => Self {
+                key: certificate.public_key().clone().into(),
+                certificate: Some(certificate),
+            },
+            Err(_) => Self {
+                key: PublicKey::from_bytes(blob)?,
+                certificate: None,
+            },
+        })
+    }
+
+    /// Verify the `signature` of the exchange `hash`, which has to be made
+    /// with the signature algorithm of the negociated _server host key_ `algorithm`.
+    pub fn verify(&self, algorithm: &Key, hash: &[u8], signature: &[u8]) -> Result<()> {
+        let signature = Signature::try_from(signature)?;
+
+        if signature.algorithm() != *algorithm {
+            tracing::warn!(
+                "Signature algorithm `{}` doesn't match the negociated host key algorithm `{algorithm}`",
+                signature.algorithm()
+            );
+
+            return Err(signature::Error::new().into());
+        }
+
+        Ok(Verifier::verify(&self.key, hash, &signature)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_rejects_a_mismatching_signature_algorithm() {
+        let key = ssh_key::PrivateKey::random(&mut rand::rng(), Key::Ed25519)
+            .expect("Unable to generate a random key");
+        let peer = PeerHostKey::decode(&key.public_key().to_bytes().expect("Unable to encode"))
+            .expect("Unable to decode the key");
+
+        let signature = signature::Signer::<Signature>::sign(&key, b"hash");
+        let signature = Vec::<u8>::try_from(signature).expect("Unable to encode the signature");
 
-    fn field<'f>(kex: &'f KexInit) -> &'f NameList<'f> {
-        &kex.server_host_key_algorithms
+        assert!(peer.verify(&Key::Ed25519, b"hash", &signature).is_ok());
+        assert!(
+            peer.verify(
+                &Key::Rsa {
+                    hash: Some(HashAlg::Sha512)
+                },
+                b"hash",
+                &signature
+            )
+            .is_err()
+        );
     }
 }
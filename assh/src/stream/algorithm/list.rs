@@ -0,0 +1,306 @@
+//! Algorithm lists in the OpenSSH syntax, as in `ssh_config(5)` and `sshd_config(5)`.
+
+use strum::IntoEnumIterator;
+
+use super::{cipher::Cipher, compress::Compress, hmac::Hmac, kex::Kex, key::Key};
+use crate::error::ListError;
+
+/// An algorithm identified by it's name in the protocol,
+/// which lists can be written in the OpenSSH syntax with [`alter`].
+pub trait Named: Sized + Clone + PartialEq {
+    /// The name of the algorithm in the protocol.
+    fn name(&self) -> &str;
+
+    /// All the supported algorithms of this kind, in order of preference.
+    fn supported() -> Vec<Self>;
+}
+
+impl Named for Kex {
+    fn name(&self) -> &str {
+        self.as_ref()
+    }
+
+    fn supported() -> Vec<Self> {
+        Self::iter().collect()
+    }
+}
+
+impl Named for Cipher {
+    fn name(&self) -> &str {
+        self.as_ref()
+    }
+
+    fn supported() -> Vec<Self> {
+        Self::iter().collect()
+    }
+}
+
+impl Named for Hmac {
+    fn name(&self) -> &str {
+        self.as_ref()
+    }
+
+    fn supported() -> Vec<Self> {
+        Self::iter().filter(Self::negociable).collect()
+    }
+}
+
+impl Named for Compress {
+    fn name(&self) -> &str {
+        self.as_ref()
+    }
+
+    fn supported() -> Vec<Self> {
+        Self::iter().collect()
+    }
+}
+
+impl Named for Key {
+    fn name(&self) -> &str {
+        self.as_str()
+    }
+
+    fn supported() -> Vec<Self> {
+        vec![
+            Key::Ed25519,
+            Key::SkEd25519,
+            Key::Ecdsa {
+                curve: ssh_key::EcdsaCurve::NistP521,
+            },
+            Key::Ecdsa {
+                curve: ssh_key::EcdsaCurve::NistP384,
+            },
+            Key::Ecdsa {
+                curve: ssh_key::EcdsaCurve::NistP256,
+            },
+            Key::SkEcdsaSha2NistP256,
+            Key::Rsa {
+                hash: Some(ssh_key::HashAlg::Sha512),
+            },
+            Key::Rsa {
+                hash: Some(ssh_key::HashAlg::Sha256),
+            },
+            #[cfg(feature = "insecure")]
+            Key::Rsa { hash: None },
+            #[cfg(feature = "insecure")]
+            Key::Dsa,
+        ]
+    }
+}
+
+/// Alter the `base` list of algorithms with a comma-separated `list` in the OpenSSH syntax.
+///
+/// Each entry is the name of an algorithm or a pattern with the `*` and `?` wildcards,
+/// matched against the [`Named::supported`] algorithms, and may be prefixed with a modifier
+/// which applies to it and the following entries:
+/// - `+` appends the algorithms to the list, unless already present,
+/// - `-` removes the algorithms from the list,
+/// - `^` moves or inserts the algorithms at the head of the list.
+///
+/// Without a leading modifier, the entries replace the `base` list altogether.
+pub fn alter<T: Named>(base: &[T], list: &str) -> Result<Vec<T>, ListError> {
+    let supported = T::supported();
+
+    let mut algorithms = base.to_vec();
+    let mut modifier = None;
+    let mut head = 0;
+
+    for (idx, entry) in list.split(',').map(str::trim).enumerate() {
+        let pattern = match entry.chars().next() {
+            Some(prefix @ ('+' | '-' | '^')) => {
+                modifier = Some(prefix);
+                &entry[1..]
+            }
+            _ => entry,
+        };
+
+        let matched = supported
+            .iter()
+            .filter(|algorithm| matches(pattern.as_bytes(), algorithm.name().as_bytes()))
+            .cloned()
+            .collect::<Vec<_>>();
+        if matched.is_empty() {
+            return Err(ListError::Unknown {
+                name: entry.into(),
+                supported: supported.iter().map(|alg| alg.name().into()).collect(),
+            });
+        }
+
+        match modifier {
+            None => {
+                if idx == 0 {
+                    algorithms.clear();
+                }
+
+                for algorithm in matched {
+                    if !algorithms.contains(&algorithm) {
+                        algorithms.push(algorithm);
+                    }
+                }
+            }
+            Some('+') => {
+                for algorithm in matched {
+                    if !algorithms.contains(&algorithm) {
+                        algorithms.push(algorithm);
+                    }
+                }
+            }
+            Some('-') => algorithms.retain(|algorithm| !matched.contains(algorithm)),
+            Some(_) => {
+                algorithms.retain(|algorithm| !matched.contains(algorithm));
+                head = head.min(algorithms.len());
+
+                for algorithm in matched {
+                    algorithms.insert(head, algorithm);
+                    head += 1;
+                }
+            }
+        }
+    }
+
+    if algorithms.is_empty() {
+        return Err(ListError::Empty(list.into()));
+    }
+
+    Ok(algorithms)
+}
+
+/// Split an OpenSSH configuration `line` into it's keyword and value.
+pub(crate) fn option(line: &str) -> Result<(&str, &str), ListError> {
+    let line = line.trim();
+
+    line.split_once(|c: char| c.is_whitespace() || c == '=')
+        .map(|(keyword, value)| {
+            (
+                keyword,
+                value.trim_start_matches(|c: char| c.is_whitespace() || c == '='),
+            )
+        })
+        .ok_or_else(|| ListError::Option(line.into()))
+}
+
+/// Alter the `base` list of compression algorithms, also accepting
+/// the `yes`, `delayed` and `no` values of the OpenSSH `Compression` option.
+pub(crate) fn compressions(base: &[Compress], list: &str) -> Result<Vec<Compress>, ListError> {
+    match list {
+        "yes" => Ok(vec![Compress::ZlibOpenssh, Compress::Zlib, Compress::None]),
+        "delayed" => Ok(vec![Compress::ZlibOpenssh, Compress::None]),
+        "no" => Ok(vec![Compress::None]),
+        list => alter(base, list),
+    }
+}
+
+/// Match the `name` against the `pattern`, with `*` matching any sequence and `?` any character.
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches(rest, name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name))) => matches(rest, name),
+        (Some((p, rest)), Some((n, name))) if p == n => matches(rest, name),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn base() -> Vec<Cipher> {
+        vec![Cipher::Aes256Gcm, Cipher::Aes128Ctr, Cipher::Aes256Ctr]
+    }
+
+    #[rstest]
+    #[case("aes128-gcm@openssh.com", vec![Cipher::Aes128Gcm])]
+    #[case("aes*-gcm@openssh.com", vec![Cipher::Aes256Gcm, Cipher::Aes128Gcm])]
+    #[case("+chacha20-poly1305@openssh.com,aes256-ctr", vec![Cipher::Aes256Gcm, Cipher::Aes128Ctr, Cipher::Aes256Ctr, Cipher::ChaCha20Poly1305])]
+    #[case("-aes*-ctr", vec![Cipher::Aes256Gcm])]
+    #[case("-aes128-ctr,+chacha20-poly1305@openssh.com", vec![Cipher::Aes256Gcm, Cipher::Aes256Ctr, Cipher::ChaCha20Poly1305])]
+    #[case("^aes256-ctr,chacha20-poly1305@openssh.com", vec![Cipher::Aes256Ctr, Cipher::ChaCha20Poly1305, Cipher::Aes256Gcm, Cipher::Aes128Ctr])]
+    fn it_alters_lists(#[case] list: &str, #[case] expected: Vec<Cipher>) {
+        assert_eq!(alter(&base(), list), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("aes512-ctr")]
+    #[case("-blowfish*")]
+    #[case("+")]
+    fn it_rejects_unknown_algorithms(#[case] list: &str) {
+        assert!(matches!(
+            alter(&base(), list),
+            Err(ListError::Unknown { supported, .. }) if supported.contains(&"aes256-ctr".to_string())
+        ));
+    }
+
+    #[cfg(not(feature = "insecure"))]
+    #[test]
+    fn it_rejects_the_none_mac() {
+        assert!(matches!(
+            alter(&[Hmac::HmacSha256], "+none"),
+            Err(ListError::Unknown { .. })
+        ));
+        assert!("none".parse::<Hmac>().is_err());
+    }
+
+    #[cfg(not(feature = "insecure"))]
+    #[test]
+    fn it_rejects_the_weak_keys() {
+        assert!(matches!(
+            alter(&[Key::Ed25519], "+ssh-rsa"),
+            Err(ListError::Unknown { .. })
+        ));
+        assert!(matches!(
+            alter(&[Key::Ed25519], "+ssh-dss"),
+            Err(ListError::Unknown { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_empty_lists() {
+        assert_eq!(
+            alter(&base(), "-aes*"),
+            Err(ListError::Empty("-aes*".into()))
+        );
+    }
+
+    #[test]
+    fn it_applies_openssh_options() {
+        let mut algorithms = crate::side::server::Algorithms::modern();
+
+        algorithms
+            .apply("Ciphers -aes*-gcm@openssh.com,+aes256-ctr")
+            .expect("Unable to apply the `Ciphers` option");
+        algorithms
+            .apply("KexAlgorithms=^curve25519-sha256")
+            .expect("Unable to apply the `KexAlgorithms` option");
+
+        assert_eq!(
+            algorithms.ciphers,
+            vec![Cipher::ChaCha20Poly1305, Cipher::Aes256Ctr]
+        );
+        assert_eq!(algorithms.kexs[0], Kex::Curve25519Sha256);
+        assert_eq!(
+            algorithms.apply("HostKeyAlgorithms ssh-ed25519"),
+            Err(ListError::Option("HostKeyAlgorithms".into()))
+        );
+    }
+
+    #[test]
+    fn it_alters_key_lists() {
+        assert_eq!(
+            alter(&[Key::Ed25519], "+rsa-sha2-*"),
+            Ok(vec![
+                Key::Ed25519,
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha512)
+                },
+                Key::Rsa {
+                    hash: Some(ssh_key::HashAlg::Sha256)
+                },
+            ])
+        );
+    }
+}
@@ -1,5 +1,3 @@
-// TODO: (feature) Gate insecure algorithms behind an `insecure` feature flag.
-
 use std::{io::Write, str::FromStr};
 
 use ssh_packet::{arch::NameList, trans::KexInit};
@@ -11,6 +9,7 @@ pub mod compress;
 pub mod hmac;
 pub mod kex;
 pub mod key;
+pub mod list;
 
 pub trait Negociate<S = ()>: Sized + FromStr {
     const ERR: Error;
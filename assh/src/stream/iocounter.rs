@@ -17,8 +17,12 @@ impl<C> IoCounter<C> {
         }
     }
 
-    pub fn count(&self) -> usize {
-        self.rx + self.tx
+    pub fn rx(&self) -> usize {
+        self.rx
+    }
+
+    pub fn tx(&self) -> usize {
+        self.tx
     }
 
     pub fn reset(&mut self) {
@@ -1,10 +1,15 @@
 //! Primitives to manipulate binary data to extract and encode
 //! messages from/to a [`Pipe`] stream.
 
+use std::time::Instant;
+
 use futures::{AsyncBufReadExt, AsyncWriteExt};
-use ssh_packet::IntoPacket;
+use ssh_packet::{IntoPacket, trans::Disconnect};
 
-use crate::{Pipe, Result};
+use crate::{
+    Error, Pipe, Result,
+    error::{DisconnectedBy, DisconnectedError, Expiry},
+};
 
 pub mod algorithm;
 
@@ -14,12 +19,18 @@ use iocounter::IoCounter;
 mod transport;
 pub use transport::Transport;
 
+mod rekey;
+pub use rekey::RekeyLimit;
+
+mod timeouts;
+pub use timeouts::{Timeouts, timeout};
+
+mod stats;
+pub use stats::Stats;
+
 #[doc(no_inline)]
 pub use ssh_packet::Packet;
 
-/// Re-key after 1GiB of exchanged data as recommended per the RFC.
-const REKEY_BYTES_THRESHOLD: usize = 0x40000000;
-
 /// A wrapper around a [`Pipe`] to interface with to the SSH binary protocol.
 pub struct Stream<S> {
     inner: IoCounter<S>,
@@ -30,79 +41,224 @@ pub struct Stream<S> {
     /// The session identifier derived from the first key exchange.
     session: Option<Vec<u8>>,
 
+    /// Limits of usage of the keys before a re-exchange.
+    limit: RekeyLimit,
+
+    /// Deadline for the data to be received from the peer.
+    deadline: Option<(Instant, Expiry)>,
+
+    /// Instant at which the current keys were set.
+    keyed: Instant,
+
+    /// Number of packets sent with the current keys.
+    txpackets: u32,
+
+    /// Number of packets received with the current keys.
+    rxpackets: u32,
+
+    /// Statistics accumulated over the previous keys.
+    stats: Stats,
+
+    /// Whether the user has been authenticated, activating delayed compression.
+    authenticated: bool,
+
+    /// Whether the strict key-exchange has been negociated with the peer.
+    strict: bool,
+
+    /// Whether the initial key-exchange is still in progress.
+    initial: bool,
+
+    /// Whether a key-exchange is in progress, restricting the messages allowed from the peer.
+    kexing: bool,
+
     /// Sequence number for the `tx` side.
     txseq: u32,
 
     /// Sequence number for the `rx` side.
     rxseq: u32,
-
-    /// A buffer for the `peek` method.
-    buffer: Option<Packet>,
 }
 
 impl<S> Stream<S>
 where
     S: Pipe,
 {
-    pub fn new(stream: S) -> Self {
+    pub fn new(stream: S, limit: RekeyLimit) -> Self {
         Self {
             inner: IoCounter::new(stream),
             transport: Default::default(),
             session: None,
+            limit,
+            deadline: None,
+            keyed: Instant::now(),
+            txpackets: 0,
+            rxpackets: 0,
+            stats: Default::default(),
+            authenticated: false,
+            strict: false,
+            initial: true,
+            kexing: false,
             txseq: 0,
             rxseq: 0,
-            buffer: None,
         }
     }
 
     pub fn should_rekey(&self) -> bool {
-        self.session.is_none() || self.inner.count() > REKEY_BYTES_THRESHOLD
+        self.session.is_none()
+            || self.inner.rx() > self.limit.bytes
+            || self.inner.tx() > self.limit.bytes
+            || self.rxpackets >= self.limit.packets
+            || self.txpackets >= self.limit.packets
+            || self
+                .limit
+                .time
+                .is_some_and(|time| self.keyed.elapsed() >= time)
     }
 
-    pub fn set_transport(&mut self, transport: Transport) {
+    pub fn set_transport(&mut self, mut transport: Transport) {
+        if self.authenticated {
+            transport.activate();
+        }
+
         self.transport = transport;
+        self.keyed = Instant::now();
+
+        self.stats.rx_bytes += self.inner.rx() as u64;
+        self.stats.tx_bytes += self.inner.tx() as u64;
+        self.stats.rx_packets += u64::from(self.rxpackets);
+        self.stats.tx_packets += u64::from(self.txpackets);
+        if self.stats.keyed.replace(self.keyed).is_some() {
+            self.stats.rekeys += 1;
+        }
+
         self.inner.reset();
+        self.txpackets = 0;
+        self.rxpackets = 0;
+
+        // Sequence numbers are reset after each `SSH_MSG_NEWKEYS` in strict key-exchange,
+        // since no other message was sent between our and the peer's one.
+        if self.strict {
+            self.txseq = 0;
+            self.rxseq = 0;
+        }
+
+        self.initial = false;
+        self.kexing = false;
+    }
+
+    /// Enter the key-exchange, after the reception of the peer's `SSH_MSG_KEXINIT`,
+    /// until the next call to [`Stream::set_transport`].
+    pub fn set_kexing(&mut self) {
+        self.kexing = true;
+    }
+
+    /// Enable the strict key-exchange, which requires the peer's `SSH_MSG_KEXINIT`
+    /// to have been the very first packet received.
+    pub fn set_strict(&mut self) -> Result<()> {
+        if self.rxseq != 1 {
+            return Err(Error::UnexpectedMessage);
+        }
+
+        self.strict = true;
+
+        Ok(())
     }
 
     pub fn with_session(&mut self, session: &[u8]) -> &[u8] {
         self.session.get_or_insert_with(|| session.to_vec())
     }
 
+    pub fn set_authenticated(&mut self) {
+        self.authenticated = true;
+        self.transport.activate();
+    }
+
+    /// Set the deadline for the data to be received from the peer,
+    /// after which the receiving methods error with [`Error::Timeout`].
+    pub fn set_deadline(&mut self, deadline: Option<(Instant, Expiry)>) {
+        self.deadline = deadline;
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// A snapshot of the cumulative statistics, including the usage of the current keys.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            rx_bytes: self.stats.rx_bytes + self.inner.rx() as u64,
+            tx_bytes: self.stats.tx_bytes + self.inner.tx() as u64,
+            rx_packets: self.stats.rx_packets + u64::from(self.rxpackets),
+            tx_packets: self.stats.tx_packets + u64::from(self.txpackets),
+            ..self.stats.clone()
+        }
+    }
+
     pub fn session_id(&self) -> Option<&[u8]> {
         self.session.as_deref()
     }
 
     pub async fn fill_buf(&mut self) -> Result<()> {
-        self.inner.fill_buf().await?;
+        timeout(self.inner.fill_buf(), self.deadline)
+            .await
+            .map_err(Error::Timeout)??;
 
         Ok(())
     }
 
-    /// Receive and decrypt a _packet_ from the peer without removing it from the queue.
-    pub async fn peek(&mut self) -> Result<&Packet> {
-        let packet = self.recv().await?;
-
-        Ok(self.buffer.insert(packet))
-    }
-
     /// Receive and decrypt a _packet_ from the peer.
+    ///
+    /// During a key-exchange, only the key-exchange messages are returned,
+    /// while the disallowed ones result in an error, as per RFC 4253 section 7.1.
     pub async fn recv(&mut self) -> Result<Packet> {
-        match self.buffer.take() {
-            Some(packet) => Ok(packet),
-            None => {
-                let data = self.transport.rx.rx(self.rxseq, &mut self.inner).await?;
+        loop {
+            let data = timeout(
+                self.transport.rx.rx(self.rxseq, &mut self.inner),
+                self.deadline,
+            )
+            .await
+            .map_err(Error::Timeout)??;
+
+            tracing::trace!(
+                "<~- #{}: ^{:#x} ({} bytes)",
+                self.rxseq,
+                data[0],
+                data.len(),
+            );
 
-                tracing::trace!(
-                    "<~- #{}: ^{:#x} ({} bytes)",
-                    self.rxseq,
-                    data[0],
-                    data.len(),
-                );
+            self.rxseq = self.rxseq.wrapping_add(1);
+            self.rxpackets = self.rxpackets.saturating_add(1);
+            self.stats.received = Some(Instant::now());
 
-                self.rxseq = self.rxseq.wrapping_add(1);
+            let packet = Packet(data);
 
-                Ok(Packet(data))
+            if self.kexing {
+                match packet[0] {
+                    20..=49 => (),
+                    1 => {
+                        let Disconnect {
+                            reason,
+                            description,
+                            ..
+                        } = packet.to()?;
+
+                        return Err(DisconnectedError {
+                            by: DisconnectedBy::Them,
+                            reason,
+                            description,
+                        }
+                        .into());
+                    }
+                    // Generic messages are not allowed during a strict initial key-exchange.
+                    2..=4 if !(self.strict && self.initial) => {
+                        tracing::debug!("Skipped message ^{:#x} during key-exchange", packet[0]);
+
+                        continue;
+                    }
+                    _ => return Err(Error::UnexpectedMessage),
+                }
             }
+
+            break Ok(packet);
         }
     }
 
@@ -124,7 +280,108 @@ where
         );
 
         self.txseq = self.txseq.wrapping_add(1);
+        self.txpackets = self.txpackets.saturating_add(1);
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::io::Cursor;
+    use ssh_packet::trans::Ignore;
+
+    use super::*;
+
+    fn keyed(limit: RekeyLimit) -> Stream<Cursor<Vec<u8>>> {
+        let mut stream = Stream::new(Cursor::new(Vec::new()), limit);
+        stream.with_session(b"session");
+        stream.set_transport(Default::default());
+
+        stream
+    }
+
+    #[async_std::test]
+    async fn it_rekeys_after_the_packets_limit() {
+        let mut stream = keyed(RekeyLimit {
+            packets: 2,
+            ..Default::default()
+        });
+
+        stream
+            .send(&Ignore::default())
+            .await
+            .expect("Unable to send");
+        assert!(!stream.should_rekey());
+
+        stream
+            .send(&Ignore::default())
+            .await
+            .expect("Unable to send");
+        assert!(stream.should_rekey());
+
+        stream.set_transport(Default::default());
+        assert!(!stream.should_rekey());
+    }
+
+    #[async_std::test]
+    async fn it_rekeys_after_the_bytes_limit() {
+        let mut stream = keyed(RekeyLimit {
+            bytes: 1024,
+            ..Default::default()
+        });
+
+        stream
+            .send(&Ignore {
+                data: vec![0; 512].into(),
+            })
+            .await
+            .expect("Unable to send");
+        assert!(!stream.should_rekey());
+
+        stream
+            .send(&Ignore {
+                data: vec![0; 512].into(),
+            })
+            .await
+            .expect("Unable to send");
+        assert!(stream.should_rekey());
+    }
+
+    #[async_std::test]
+    async fn it_accumulates_stats_across_rekeys() {
+        let mut stream = keyed(Default::default());
+
+        stream
+            .send(&Ignore::default())
+            .await
+            .expect("Unable to send");
+        let before = stream.stats();
+
+        stream.set_transport(Default::default());
+        stream
+            .send(&Ignore::default())
+            .await
+            .expect("Unable to send");
+        let after = stream.stats();
+
+        assert_eq!(before.tx_packets, 1);
+        assert_eq!(before.rekeys, 0);
+        assert_eq!(after.tx_packets, 2);
+        assert_eq!(after.tx_bytes, 2 * before.tx_bytes);
+        assert_eq!(after.rekeys, 1);
+        assert!(after.keyed > before.keyed);
+    }
+
+    #[async_std::test]
+    async fn it_rekeys_after_the_time_limit() {
+        let stream = keyed(RekeyLimit {
+            time: Some(Duration::ZERO),
+            ..Default::default()
+        });
+
+        assert!(stream.should_rekey());
+    }
+}
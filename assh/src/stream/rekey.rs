@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// Limits of usage of the session keys, triggering a key re-exchange once any is exceeded,
+/// akin to OpenSSH's `RekeyLimit` option.
+///
+/// The limits apply to each direction independently.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct RekeyLimit {
+    /// Maximum number of bytes transferred with the same keys,
+    /// defaulting to 1GiB as recommended in RFC 4253.
+    pub bytes: usize,
+
+    /// Maximum number of packets transferred with the same keys,
+    /// defaulting to 2^31 to stay below the 2^32 packets recommended in RFC 4344.
+    pub packets: u32,
+
+    /// Maximum lifetime of the session keys, unlimited by default.
+    ///
+    /// This limit is only checked when sending or receiving packets.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub time: Option<Duration>,
+}
+
+impl Default for RekeyLimit {
+    fn default() -> Self {
+        Self {
+            bytes: 0x40000000,
+            packets: 1 << 31,
+            time: None,
+        }
+    }
+}
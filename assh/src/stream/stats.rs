@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+/// A snapshot of the cumulative statistics of a session.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    /// Number of bytes received from the peer.
+    pub rx_bytes: u64,
+
+    /// Number of bytes sent to the peer.
+    pub tx_bytes: u64,
+
+    /// Number of packets received from the peer.
+    pub rx_packets: u64,
+
+    /// Number of packets sent to the peer.
+    pub tx_packets: u64,
+
+    /// Number of key re-exchanges completed, after the initial key-exchange.
+    pub rekeys: u64,
+
+    /// Instant at which the last key-exchange completed, if any.
+    pub keyed: Option<Instant>,
+
+    /// Instant at which the last packet was received from the peer, if any.
+    pub received: Option<Instant>,
+}
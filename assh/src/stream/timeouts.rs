@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use futures::{
+    Future,
+    future::{self, Either},
+};
+use futures_timer::Delay;
+
+use crate::error::Expiry;
+
+/// Deadlines of the session, after which the peer is disconnected,
+/// akin to OpenSSH's `LoginGraceTime` and `ClientAliveInterval` options.
+///
+/// The deadlines are enforced while awaiting data from the peer, and are disabled when `None`.
+///
+/// With the `serde` feature, the durations are deserialized in the `humantime` format, like `90s`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Timeouts {
+    /// Maximum duration of the identification strings exchange,
+    /// defaulting to 60 seconds.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub id: Option<Duration>,
+
+    /// Maximum duration of each key-exchange round, from the sending of our `SSH_MSG_KEXINIT`
+    /// to the reception of the peer's `SSH_MSG_NEWKEYS`, defaulting to 60 seconds.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub kex: Option<Duration>,
+
+    /// Maximum duration from the start of the session to the authentication of the user,
+    /// as marked by [`Session::set_authenticated`](crate::Session::set_authenticated).
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub auth: Option<Duration>,
+
+    /// Maximum duration without receiving any packet from the peer, unlimited by default.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub idle: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            id: Some(Duration::from_secs(60)),
+            kex: Some(Duration::from_secs(60)),
+            auth: None,
+            idle: None,
+        }
+    }
+}
+
+/// Run the `future` to completion, unless the `deadline` is reached beforehand.
+pub async fn timeout<F: Future>(
+    future: F,
+    deadline: Option<(Instant, Expiry)>,
+) -> Result<F::Output, Expiry> {
+    let Some((at, expiry)) = deadline else {
+        return Ok(future.await);
+    };
+
+    let delay = Delay::new(at.saturating_duration_since(Instant::now()));
+
+    match future::select(std::pin::pin!(future), delay).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(expiry),
+    }
+}
@@ -25,7 +25,7 @@ impl Transport {
         session_id: &[u8],
     ) -> Self {
         let tx = TxTransport {
-            compress: client.compress,
+            compress: compress::CompressState::new(&client.compress),
             cipher: cipher::EncState::new::<b'A', b'C', H>(
                 &client.cipher,
                 secret,
@@ -36,7 +36,7 @@ impl Transport {
         };
 
         let rx = RxTransport {
-            compress: server.compress,
+            compress: compress::DecompressState::new(&server.compress),
             cipher: cipher::DecState::new::<b'B', b'D', H>(
                 &server.cipher,
                 secret,
@@ -57,7 +57,7 @@ impl Transport {
         session_id: &[u8],
     ) -> Self {
         let tx = TxTransport {
-            compress: server.compress,
+            compress: compress::CompressState::new(&server.compress),
             cipher: cipher::EncState::new::<b'B', b'D', H>(
                 &server.cipher,
                 secret,
@@ -68,7 +68,7 @@ impl Transport {
         };
 
         let rx = RxTransport {
-            compress: client.compress,
+            compress: compress::DecompressState::new(&client.compress),
             cipher: cipher::DecState::new::<b'A', b'C', H>(
                 &client.cipher,
                 secret,
@@ -80,11 +80,17 @@ impl Transport {
 
         Self { tx, rx }
     }
+
+    /// Activate the delayed compression algorithms in both directions.
+    pub fn activate(&mut self) {
+        self.tx.compress.activate();
+        self.rx.compress.activate();
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct TxTransport {
-    compress: compress::Compress,
+    compress: compress::CompressState,
     cipher: cipher::EncState,
     hmac: hmac::State,
 }
@@ -96,7 +102,8 @@ impl TxTransport {
 
         let align = self.cipher.block_size().max(MIN_ALIGN);
 
-        let size = if self.hmac.etm() {
+        // The length isn't aligned when it's authenticated but not encrypted alongside the packet.
+        let size = if self.hmac.etm() || self.cipher.authenticated() {
             std::mem::size_of::<u8>() + payload
         } else {
             std::mem::size_of::<u32>() + std::mem::size_of::<u8>() + payload
@@ -142,21 +149,30 @@ impl TxTransport {
         let buf = self.pad(compressed)?;
         let mut buf = [(buf.len() as u32).to_be_bytes().to_vec(), buf].concat();
 
-        let mac;
-        if self.hmac.etm() {
-            // Encrypt-Then-MAC
+        if self.cipher.authenticated() {
+            // Authenticated Encryption
 
-            self.cipher.encrypt(&mut buf[4..])?;
-            mac = self.hmac.compute(seq, &buf);
+            let tag = self.cipher.seal(seq, &mut buf)?;
+
+            writer.write_all(&buf).await?;
+            writer.write_all(&tag).await?;
         } else {
-            // MAC-Then-Encrypt
+            let mac;
+            if self.hmac.etm() {
+                // Encrypt-Then-MAC
 
-            mac = self.hmac.compute(seq, &buf);
-            self.cipher.encrypt(&mut buf[..])?;
-        }
+                self.cipher.encrypt(&mut buf[4..])?;
+                mac = self.hmac.compute(seq, &buf);
+            } else {
+                // MAC-Then-Encrypt
+
+                mac = self.hmac.compute(seq, &buf);
+                self.cipher.encrypt(&mut buf[..])?;
+            }
 
-        writer.write_all(&buf).await?;
-        writer.write_all(&mac).await?;
+            writer.write_all(&buf).await?;
+            writer.write_all(&mac).await?;
+        }
 
         Ok(())
     }
@@ -164,13 +180,56 @@ impl TxTransport {
 
 #[derive(Debug, Default)]
 pub struct RxTransport {
-    compress: compress::Compress,
+    compress: compress::DecompressState,
     cipher: cipher::DecState,
     hmac: hmac::State,
 }
 
 impl RxTransport {
-    pub async fn rx(&mut self, seq: u32, mut reader: impl AsyncRead + Unpin) -> Result<Vec<u8>> {
+    fn check_len(len: u32) -> Result<()> {
+        if len as usize > Packet::MAX_SIZE {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("payload size too large, {len} > {}", Packet::MAX_SIZE),
+            ))?
+        }
+
+        Ok(())
+    }
+
+    async fn rx_aead(
+        &mut self,
+        seq: u32,
+        mut reader: impl AsyncRead + Unpin,
+    ) -> Result<(u32, Vec<u8>)> {
+        let mut buf = vec![0; 4];
+        reader.read_exact(&mut buf[..]).await?;
+
+        let len = self.cipher.length(
+            seq,
+            buf[..4]
+                .try_into()
+                .expect("the buffer of size 4 is not of size 4"),
+        )?;
+        Self::check_len(len)?;
+
+        // read the rest of the data from the reader
+        buf.resize(std::mem::size_of_val(&len) + len as usize, 0);
+        reader.read_exact(&mut buf[4..]).await?;
+
+        let mut tag = [0; cipher::TAG_SIZE];
+        reader.read_exact(&mut tag[..]).await?;
+
+        self.cipher.open(seq, &mut buf, &tag)?;
+
+        Ok((len, buf))
+    }
+
+    async fn rx_hmac(
+        &mut self,
+        seq: u32,
+        mut reader: impl AsyncRead + Unpin,
+    ) -> Result<(u32, Vec<u8>)> {
         let mut buf = vec![0; self.cipher.block_size()];
         reader.read_exact(&mut buf[..]).await?;
 
@@ -183,13 +242,7 @@ impl RxTransport {
                 .try_into()
                 .expect("the buffer of size 4 is not of size 4"),
         );
-
-        if len as usize > Packet::MAX_SIZE {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("payload size too large, {len} > {}", Packet::MAX_SIZE),
-            ))?
-        }
+        Self::check_len(len)?;
 
         // read the rest of the data from the reader
         buf.resize(std::mem::size_of_val(&len) + len as usize, 0);
@@ -208,6 +261,16 @@ impl RxTransport {
             self.hmac.verify(seq, &buf, &mac)?;
         }
 
+        Ok((len, buf))
+    }
+
+    pub async fn rx(&mut self, seq: u32, mut reader: impl AsyncRead + Unpin) -> Result<Vec<u8>> {
+        let (len, buf) = if self.cipher.authenticated() {
+            self.rx_aead(seq, &mut reader).await?
+        } else {
+            self.rx_hmac(seq, &mut reader).await?
+        };
+
         let (padlen, mut decrypted) = buf[4..].split_first().ok_or_else(|| {
             io::Error::new(
                 io::ErrorKind::UnexpectedEof,
@@ -1,28 +1,62 @@
 use std::net::SocketAddr;
 
-use async_std::{net::TcpListener, stream::StreamExt};
+use async_std::{
+    net::{TcpListener, TcpStream},
+    stream::StreamExt,
+};
 use futures::io::BufReader;
 
-use assh::{Result, Session, side::server::Server};
+use assh::{
+    Error, Negociated, Result, Session,
+    side::{client::Client, server::Server},
+};
 use ssh_packet::{
     Packet,
+    arch::ascii,
     connect::{ChannelOpen, ChannelOpenConfirmation},
-    trans::{Ignore, ServiceAccept, ServiceRequest},
+    trans::{Disconnect, DisconnectReason, Ignore, ServiceAccept, ServiceRequest},
     userauth::{self, Request},
 };
 
+#[allow(dead_code, reason = "not every test binary uses the default server")]
 pub async fn server() -> Result<(SocketAddr, impl futures::Future<Output = Result<Packet>>)> {
+    server_with(Default::default()).await
+}
+
+pub async fn server_with(
+    server: Server,
+) -> Result<(SocketAddr, impl futures::Future<Output = Result<Packet>>)> {
+    serve(server, false).await
+}
+
+/// Spawn a server initiating a key re-exchange right before accepting the authentication.
+#[allow(dead_code, reason = "not every test binary rekeys upon authentication")]
+pub async fn server_rekeying_on_auth(
+    server: Server,
+) -> Result<(SocketAddr, impl futures::Future<Output = Result<Packet>>)> {
+    serve(server, true).await
+}
+
+async fn serve(
+    server: Server,
+    rekey_on_auth: bool,
+) -> Result<(SocketAddr, impl futures::Future<Output = Result<Packet>>)> {
     let socket = TcpListener::bind(("127.0.0.1", 0)).await?;
     let addr = socket.local_addr()?;
 
     let handle = async_std::task::spawn_local(async move {
         let stream = BufReader::new(socket.incoming().next().await.unwrap()?);
 
-        let server = Server {
-            keys: vec![
-                ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519).unwrap(),
-            ],
-            ..Default::default()
+        let server = if server.keys.is_empty() && server.signers.is_empty() {
+            Server {
+                keys: vec![
+                    ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)
+                        .unwrap(),
+                ],
+                ..server
+            }
+        } else {
+            server
         };
         let mut session = Session::new(stream, server).await?;
 
@@ -46,7 +80,12 @@ pub async fn server() -> Result<(SocketAddr, impl futures::Future<Output = Resul
             .await?;
 
         if session.recv().await?.to::<Request>().is_ok() {
+            if rekey_on_auth {
+                session.rekey().await?;
+            }
+
             session.send(&userauth::Success).await?;
+            session.set_authenticated();
         }
 
         if let Ok(open) = session.recv().await?.to::<ChannelOpen>() {
@@ -65,3 +104,56 @@ pub async fn server() -> Result<(SocketAddr, impl futures::Future<Output = Resul
 
     Ok((addr, handle))
 }
+
+/// Connect the `client` to a spawned `server` until the service request is accepted,
+/// and return the algorithms negociated in the initial key-exchange.
+#[allow(
+    dead_code,
+    reason = "not every test binary inspects the negociated algorithms"
+)]
+pub async fn connect_and_negociate(client: Client, server: Server) -> Result<Negociated> {
+    let (addr, handle) = server_with(server).await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(
+        stream,
+        Client {
+            port: addr.port(),
+            ..client
+        },
+    )
+    .await?;
+
+    if let Err(err) = client
+        .send(&ServiceRequest {
+            service_name: ascii!("ssh-userauth"),
+        })
+        .await
+    {
+        assert!(handle.await.is_err());
+
+        return Err(err);
+    }
+    client
+        .recv()
+        .await?
+        .to::<ServiceAccept>()
+        .expect("Service refused by peer");
+
+    let negociated = client
+        .negociated()
+        .expect("The key-exchange has not been completed")
+        .clone();
+
+    client
+        .send(&Disconnect {
+            reason: DisconnectReason::ByApplication,
+            description: "bbbb".into(),
+            language: Default::default(),
+        })
+        .await?;
+
+    assert!(matches!(handle.await, Err(Error::Disconnected(_))));
+
+    Ok(negociated)
+}
@@ -3,26 +3,77 @@
 use async_std::process::Command;
 use rstest::rstest;
 
-use assh::Result;
+use assh::{
+    Result,
+    side::server::{Algorithms, Server},
+};
 use ssh_packet::connect::ChannelRequest;
 
 mod common;
 
 #[rstest]
-#[case("3des-cbc", "hmac-md5", "curve25519-sha256")]
-#[case("aes128-cbc", "hmac-sha1", "curve25519-sha256")]
-#[case("aes192-cbc", "hmac-sha2-256", "curve25519-sha256")]
-#[case("aes256-cbc", "hmac-sha2-512", "curve25519-sha256")]
-#[case("3des-cbc", "hmac-md5-etm@openssh.com", "curve25519-sha256")]
-#[case("aes128-cbc", "hmac-sha1-etm@openssh.com", "curve25519-sha256")]
-#[case("aes192-cbc", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")]
-#[case("aes256-cbc", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")]
-#[case("aes128-ctr", "hmac-sha1", "curve25519-sha256")]
+#[cfg_attr(
+    feature = "insecure",
+    case("3des-cbc", "hmac-md5", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-cbc", "hmac-sha1", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes192-cbc", "hmac-sha2-256", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes256-cbc", "hmac-sha2-512", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("3des-cbc", "hmac-md5-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-cbc", "hmac-sha1-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes192-cbc", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes256-cbc", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-ctr", "hmac-sha1", "curve25519-sha256")
+)]
 #[case("aes192-ctr", "hmac-sha2-256", "curve25519-sha256")]
 #[case("aes256-ctr", "hmac-sha2-512", "curve25519-sha256")]
-#[case("aes128-ctr", "hmac-sha1-etm@openssh.com", "curve25519-sha256")]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-ctr", "hmac-sha1-etm@openssh.com", "curve25519-sha256")
+)]
 #[case("aes192-ctr", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")]
 #[case("aes256-ctr", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")]
+#[case("aes128-ctr", "umac-64@openssh.com", "curve25519-sha256")]
+#[case("aes256-ctr", "umac-128@openssh.com", "curve25519-sha256")]
+#[case("aes128-ctr", "umac-64-etm@openssh.com", "curve25519-sha256")]
+#[case("aes256-ctr", "umac-128-etm@openssh.com", "curve25519-sha256")]
+#[case("chacha20-poly1305@openssh.com", "hmac-sha2-256", "curve25519-sha256")]
+#[case("aes256-gcm@openssh.com", "hmac-sha2-512", "curve25519-sha256")]
+#[case("aes128-gcm@openssh.com", "hmac-sha2-256", "curve25519-sha256")]
+#[ignore = "requires OpenSSH 9.9 or later"]
+#[case("aes256-ctr", "hmac-sha2-256", "mlkem768x25519-sha256")]
+#[case("aes256-ctr", "hmac-sha2-512", "sntrup761x25519-sha512")]
+#[case("aes256-ctr", "hmac-sha2-512", "sntrup761x25519-sha512@openssh.com")]
+#[case("aes128-ctr", "hmac-sha2-256", "ecdh-sha2-nistp256")]
+#[case("aes192-ctr", "hmac-sha2-512", "ecdh-sha2-nistp384")]
+#[case("aes256-ctr", "hmac-sha2-512", "ecdh-sha2-nistp521")]
+#[case("aes256-ctr", "hmac-sha2-256", "diffie-hellman-group14-sha256")]
+#[case("aes256-ctr", "hmac-sha2-512", "diffie-hellman-group16-sha512")]
+#[case("aes256-ctr", "hmac-sha2-512", "diffie-hellman-group18-sha512")]
+#[case("aes256-ctr", "hmac-sha2-256", "diffie-hellman-group-exchange-sha256")]
 #[async_std::test]
 async fn against_openssh_client(
     #[case] cipher: &str,
@@ -34,7 +85,26 @@ async fn against_openssh_client(
         .try_init()
         .ok();
 
-    let (addr, handle) = common::server().await?;
+    let supported = Command::new("ssh").arg("-Qkex").output().await?;
+    if !String::from_utf8_lossy(&supported.stdout)
+        .lines()
+        .any(|line| line == kex)
+    {
+        eprintln!("Skipping kex::{kex}, not supported by the local OpenSSH client");
+
+        return Ok(());
+    }
+
+    let (addr, handle) = common::server_with(Server {
+        algorithms: Algorithms {
+            kexs: vec![kex.parse()?],
+            ciphers: vec![cipher.parse()?],
+            macs: vec![mac.parse()?],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
 
     tracing::info!("cipher::{cipher}, mac::{mac}, kex::{kex}, bound to {addr}");
 
@@ -59,3 +129,103 @@ async fn against_openssh_client(
 
     Ok(())
 }
+
+#[rstest]
+#[case("zlib@openssh.com")]
+#[case("zlib")]
+#[async_std::test]
+async fn against_openssh_client_with_compression(
+    #[case] compress: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server_with(Server {
+        algorithms: Algorithms {
+            compressions: vec![compress.parse()?],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    tracing::info!("compress::{compress}, bound to {addr}");
+
+    let mut client = Command::new("ssh")
+        .arg("-oStrictHostKeyChecking=no")
+        .arg("-oUserKnownHostsFile=/dev/null")
+        .arg("-oRekeyLimit=1K")
+        .arg("-oCompression=yes")
+        .arg(format!("-p{}", addr.port()))
+        .arg("user@127.0.0.1")
+        .arg("/bin/bash")
+        .spawn()?;
+
+    let message = handle.await?;
+    let status = client.status().await?;
+
+    tracing::info!("message: {message:?}, {status}");
+
+    assert!(message.to::<ChannelRequest>().is_ok());
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn against_openssh_client_with_certificate() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let ca = ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?;
+    let key = ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?;
+
+    let mut builder = ssh_key::certificate::Builder::new_with_random_nonce(
+        &mut rand::rng(),
+        key.public_key().key_data().clone(),
+        0,
+        u64::MAX,
+    )?;
+    builder.cert_type(ssh_key::certificate::CertType::Host)?;
+    builder.key_id("assh")?;
+    builder.valid_principal("127.0.0.1")?;
+    let certificate = builder.sign(&ca)?;
+
+    let known_hosts = std::env::temp_dir().join(format!("assh-known-hosts-{}", std::process::id()));
+    std::fs::write(
+        &known_hosts,
+        format!("@cert-authority * {}\n", ca.public_key().to_openssh()?),
+    )?;
+
+    let (addr, handle) = common::server_with(Server {
+        keys: vec![key],
+        certificates: vec![certificate],
+        ..Default::default()
+    })
+    .await?;
+
+    tracing::info!("bound to {addr}");
+
+    let mut client = Command::new("ssh")
+        .arg("-oStrictHostKeyChecking=yes")
+        .arg(format!("-oUserKnownHostsFile={}", known_hosts.display()))
+        .arg("-oHostKeyAlgorithms=ssh-ed25519-cert-v01@openssh.com")
+        .arg("-oRekeyLimit=1K")
+        .arg(format!("-p{}", addr.port()))
+        .arg("user@127.0.0.1")
+        .arg("/bin/bash")
+        .spawn()?;
+
+    let message = handle.await;
+    let status = client.status().await?;
+    std::fs::remove_file(&known_hosts).ok();
+
+    tracing::info!("message: {message:?}, {status}");
+
+    assert!(message?.to::<ChannelRequest>().is_ok());
+
+    Ok(())
+}
@@ -1,36 +1,89 @@
 #![allow(clippy::unwrap_used)]
 
+use std::{sync::Arc, time::Duration};
+
 use async_std::net::TcpStream;
 use futures::io::BufReader;
 use rstest::rstest;
 
 use assh::{
     Error, Result, Session,
-    side::client::{Algorithms, Client},
+    error::{DisconnectedBy, DisconnectedError},
+    side::{
+        client::{Algorithms, Client, Pinned},
+        server,
+    },
 };
 use ssh_packet::{
     connect::{ChannelOpen, ChannelOpenConfirmation, ChannelOpenContext},
-    trans::{Disconnect, ServiceAccept, ServiceRequest},
+    trans::{Disconnect, DisconnectReason, ServiceAccept, ServiceRequest},
     userauth::{self, Success},
 };
 
 mod common;
 
 #[rstest]
-#[case("3des-cbc", "hmac-md5", "curve25519-sha256")]
-#[case("aes128-cbc", "hmac-sha1", "curve25519-sha256")]
-#[case("aes192-cbc", "hmac-sha2-256", "curve25519-sha256")]
-#[case("aes256-cbc", "hmac-sha2-512", "curve25519-sha256")]
-#[case("3des-cbc", "hmac-md5-etm@openssh.com", "curve25519-sha256")]
-#[case("aes128-cbc", "hmac-sha1-etm@openssh.com", "curve25519-sha256")]
-#[case("aes192-cbc", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")]
-#[case("aes256-cbc", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")]
-#[case("aes128-ctr", "hmac-sha1", "curve25519-sha256")]
+#[cfg_attr(
+    feature = "insecure",
+    case("3des-cbc", "hmac-md5", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-cbc", "hmac-sha1", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes192-cbc", "hmac-sha2-256", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes256-cbc", "hmac-sha2-512", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("3des-cbc", "hmac-md5-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-cbc", "hmac-sha1-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes192-cbc", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes256-cbc", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")
+)]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-ctr", "hmac-sha1", "curve25519-sha256")
+)]
 #[case("aes192-ctr", "hmac-sha2-256", "curve25519-sha256")]
 #[case("aes256-ctr", "hmac-sha2-512", "curve25519-sha256")]
-#[case("aes128-ctr", "hmac-sha1-etm@openssh.com", "curve25519-sha256")]
+#[cfg_attr(
+    feature = "insecure",
+    case("aes128-ctr", "hmac-sha1-etm@openssh.com", "curve25519-sha256")
+)]
 #[case("aes192-ctr", "hmac-sha2-256-etm@openssh.com", "curve25519-sha256")]
 #[case("aes256-ctr", "hmac-sha2-512-etm@openssh.com", "curve25519-sha256")]
+#[case("aes128-ctr", "umac-64@openssh.com", "curve25519-sha256")]
+#[case("aes256-ctr", "umac-128@openssh.com", "curve25519-sha256")]
+#[case("aes128-ctr", "umac-64-etm@openssh.com", "curve25519-sha256")]
+#[case("aes256-ctr", "umac-128-etm@openssh.com", "curve25519-sha256")]
+#[case("chacha20-poly1305@openssh.com", "hmac-sha2-256", "curve25519-sha256")]
+#[case("aes256-gcm@openssh.com", "hmac-sha2-512", "curve25519-sha256")]
+#[case("aes128-gcm@openssh.com", "hmac-sha2-256", "curve25519-sha256")]
+#[case("aes256-ctr", "hmac-sha2-256", "mlkem768x25519-sha256")]
+#[case("aes256-ctr", "hmac-sha2-512", "sntrup761x25519-sha512")]
+#[case("aes256-ctr", "hmac-sha2-512", "sntrup761x25519-sha512@openssh.com")]
+#[case("aes128-ctr", "hmac-sha2-256", "ecdh-sha2-nistp256")]
+#[case("aes192-ctr", "hmac-sha2-512", "ecdh-sha2-nistp384")]
+#[case("aes256-ctr", "hmac-sha2-512", "ecdh-sha2-nistp521")]
+#[case("aes256-ctr", "hmac-sha2-256", "diffie-hellman-group14-sha256")]
+#[case("aes256-ctr", "hmac-sha2-512", "diffie-hellman-group16-sha512")]
+#[case("aes256-ctr", "hmac-sha2-512", "diffie-hellman-group18-sha512")]
+#[case("aes256-ctr", "hmac-sha2-256", "diffie-hellman-group-exchange-sha256")]
 #[async_std::test]
 async fn end_to_end(
     #[case] cipher: &str,
@@ -44,7 +97,16 @@ async fn end_to_end(
         .try_init()
         .ok();
 
-    let (addr, handle) = common::server().await?;
+    let (addr, handle) = common::server_with(server::Server {
+        algorithms: server::Algorithms {
+            kexs: vec![kex.parse()?],
+            ciphers: vec![cipher.parse()?],
+            macs: vec![mac.parse()?],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
 
     tracing::info!("cipher::{cipher}, mac::{mac}, kex::{kex}, bound to {addr}");
 
@@ -63,6 +125,404 @@ async fn end_to_end(
     )
     .await?;
 
+    client
+        .send(&ServiceRequest {
+            service_name: ascii!("ssh-userauth"),
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<ServiceAccept>()
+        .expect("Service refused by peer");
+
+    assert!(
+        client
+            .extensions()
+            .server_sig_algs()
+            .is_some_and(|algs| !algs.is_empty())
+    );
+    assert!(client.extensions().ping());
+
+    let negociated = client
+        .negociated()
+        .expect("The key-exchange has not been completed");
+    assert_eq!(negociated.kex.as_ref(), kex);
+    assert_eq!(negociated.key, ssh_key::Algorithm::Ed25519);
+    assert_eq!(negociated.host_key.algorithm(), negociated.key);
+    for direction in [&negociated.client_to_server, &negociated.server_to_client] {
+        assert_eq!(direction.cipher.as_ref(), cipher);
+        assert_eq!(
+            direction.hmac.as_ref(),
+            if direction.cipher.authenticated() {
+                "none"
+            } else {
+                mac
+            }
+        );
+        assert_eq!(direction.compress.as_ref(), "zlib@openssh.com");
+    }
+
+    client
+        .send(&userauth::Request {
+            username: "user".into(),
+            service_name: ascii!("?"),
+            method: ssh_packet::userauth::Method::None,
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<Success>()
+        .expect("Auth refused by peer");
+    client.set_authenticated();
+
+    client
+        .send(&ChannelOpen {
+            sender_channel: 0,
+            initial_window_size: 128,
+            maximum_packet_size: 128,
+            context: ChannelOpenContext::Session,
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<ChannelOpenConfirmation>()
+        .expect("Channel open refused by peer");
+
+    client
+        .send(&Disconnect {
+            reason: ssh_packet::trans::DisconnectReason::ByApplication,
+            description: "bbbb".into(),
+            language: Default::default(),
+        })
+        .await?;
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(matches!(message, Err(Error::Disconnected(_))));
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn end_to_end_with_rejected_host_key() -> Result<(), Box<dyn std::error::Error>> {
+    use ssh_packet::arch::ascii;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server().await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(
+        stream,
+        Client {
+            verifier: Arc::new(Pinned::default()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let result = client
+        .send(&ServiceRequest {
+            service_name: ascii!("ssh-userauth"),
+        })
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Disconnected(DisconnectedError {
+            reason: DisconnectReason::HostKeyNotVerifiable,
+            ..
+        }))
+    ));
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(matches!(message, Err(Error::Disconnected(_))));
+
+    Ok(())
+}
+
+#[rstest]
+#[case::modern(
+    Algorithms::modern(),
+    server::Algorithms::modern(),
+    ssh_key::Algorithm::Ed25519,
+    "mlkem768x25519-sha256",
+    "chacha20-poly1305@openssh.com"
+)]
+#[case::compatible(
+    Algorithms::compatible(),
+    server::Algorithms::compatible(),
+    ssh_key::Algorithm::Ed25519,
+    "mlkem768x25519-sha256",
+    "chacha20-poly1305@openssh.com"
+)]
+#[case::fips(
+    Algorithms::fips(),
+    server::Algorithms::fips(),
+    ssh_key::Algorithm::Ecdsa { curve: ssh_key::EcdsaCurve::NistP256 },
+    "ecdh-sha2-nistp256",
+    "aes256-gcm@openssh.com"
+)]
+#[case::modern_to_compatible(
+    Algorithms::modern(),
+    server::Algorithms::compatible(),
+    ssh_key::Algorithm::Ed25519,
+    "mlkem768x25519-sha256",
+    "chacha20-poly1305@openssh.com"
+)]
+#[case::fips_to_compatible(
+    Algorithms::fips(),
+    server::Algorithms::compatible(),
+    ssh_key::Algorithm::Ecdsa { curve: ssh_key::EcdsaCurve::NistP256 },
+    "ecdh-sha2-nistp256",
+    "aes256-gcm@openssh.com"
+)]
+#[async_std::test]
+async fn end_to_end_with_profile(
+    #[case] client: Algorithms,
+    #[case] server: server::Algorithms,
+    #[case] key: ssh_key::Algorithm,
+    #[case] kex: &str,
+    #[case] cipher: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let negociated = common::connect_and_negociate(
+        Client {
+            algorithms: client,
+            ..Default::default()
+        },
+        server::Server {
+            keys: vec![ssh_key::PrivateKey::random(&mut rand::rng(), key.clone())?],
+            algorithms: server,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    assert_eq!(negociated.kex.as_ref(), kex);
+    assert_eq!(negociated.key, key);
+    assert_eq!(negociated.client_to_server.cipher.as_ref(), cipher);
+    assert_eq!(negociated.server_to_client.cipher.as_ref(), cipher);
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn end_to_end_with_host_key_signer() -> Result<(), Box<dyn std::error::Error>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use server::{HostKeySigner, Signing};
+
+    /// A stand-in for a remote signer, counting the signatures it made.
+    #[derive(Debug)]
+    struct Remote {
+        key: ssh_key::PrivateKey,
+        signatures: AtomicUsize,
+    }
+
+    impl HostKeySigner for Remote {
+        fn public_key(&self) -> &ssh_key::PublicKey {
+            self.key.public_key()
+        }
+
+        fn sign<'a>(&'a self, algorithm: &'a ssh_key::Algorithm, data: &'a [u8]) -> Signing<'a> {
+            self.signatures.fetch_add(1, Ordering::SeqCst);
+
+            HostKeySigner::sign(&self.key, algorithm, data)
+        }
+    }
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let signer = Arc::new(Remote {
+        key: ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?,
+        signatures: AtomicUsize::new(0),
+    });
+    let public_key = signer.public_key().clone();
+
+    let negociated = common::connect_and_negociate(
+        Client {
+            verifier: Arc::new(Pinned::new([public_key.fingerprint(Default::default())])),
+            ..Default::default()
+        },
+        server::Server {
+            signers: vec![signer.clone()],
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    assert_eq!(negociated.host_key, public_key);
+    assert_eq!(signer.signatures.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::sha512(ssh_key::HashAlg::Sha512)]
+#[case::sha256(ssh_key::HashAlg::Sha256)]
+#[async_std::test]
+async fn end_to_end_with_rsa_host_key(
+    #[case] hash: ssh_key::HashAlg,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let key = ssh_key::PrivateKey::from(ssh_key::private::RsaKeypair::random(
+        &mut rand::rng(),
+        2048,
+    )?);
+    let algorithm = ssh_key::Algorithm::Rsa { hash: Some(hash) };
+
+    let negociated = common::connect_and_negociate(
+        Client {
+            algorithms: Algorithms {
+                keys: vec![algorithm.clone()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        server::Server {
+            keys: vec![key],
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    assert_eq!(negociated.key, algorithm);
+
+    Ok(())
+}
+
+#[rstest]
+#[case::trusted("127.0.0.1", u64::MAX, true, true)]
+#[case::other_principal("example.com", u64::MAX, true, false)]
+#[case::expired("127.0.0.1", 1, true, false)]
+#[case::untrusted_authority("127.0.0.1", u64::MAX, false, false)]
+#[async_std::test]
+async fn end_to_end_with_host_certificate(
+    #[case] principal: &str,
+    #[case] valid_before: u64,
+    #[case] trusted: bool,
+    #[case] accepted: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use assh::side::client::known_hosts::{KnownHosts, Policy};
+    use ssh_key::certificate::{Builder, CertType};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let ca = ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?;
+    let other = ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?;
+    let key = ssh_key::PrivateKey::random(&mut rand::rng(), ssh_key::Algorithm::Ed25519)?;
+
+    let mut builder = Builder::new_with_random_nonce(
+        &mut rand::rng(),
+        key.public_key().key_data().clone(),
+        0,
+        valid_before,
+    )?;
+    builder.cert_type(CertType::Host)?;
+    builder.valid_principal(principal)?;
+    let certificate = builder.sign(&ca)?;
+
+    let known_hosts = KnownHosts::parse(
+        &format!(
+            "@cert-authority * {}\n",
+            if trusted { &ca } else { &other }
+                .public_key()
+                .to_openssh()?
+        ),
+        Policy::Yes,
+    )?;
+
+    let result = common::connect_and_negociate(
+        Client {
+            host: "127.0.0.1".into(),
+            verifier: Arc::new(known_hosts),
+            ..Default::default()
+        },
+        server::Server {
+            keys: vec![key],
+            certificates: vec![certificate.clone()],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    if !accepted {
+        assert!(matches!(
+            result,
+            Err(Error::Disconnected(DisconnectedError {
+                reason: DisconnectReason::HostKeyNotVerifiable,
+                ..
+            }))
+        ));
+
+        return Ok(());
+    }
+
+    let negociated = result?;
+    assert_eq!(negociated.key, ssh_key::Algorithm::Ed25519);
+    assert_eq!(negociated.certificate.as_ref(), Some(&certificate));
+
+    Ok(())
+}
+
+#[rstest]
+#[case::on_demand(true)]
+#[case::by_limit(false)]
+#[async_std::test]
+async fn end_to_end_with_rekey(#[case] on_demand: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use assh::side::client::RekeyLimit;
+    use ssh_packet::arch::ascii;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server().await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(
+        stream,
+        Client {
+            rekey_limit: if on_demand {
+                Default::default()
+            } else {
+                RekeyLimit {
+                    packets: 2,
+                    ..Default::default()
+                }
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
     client
         .send(&ServiceRequest {
             service_name: ascii!("ssh-userauth"),
@@ -86,7 +546,13 @@ async fn end_to_end(
         .await?
         .to::<Success>()
         .expect("Auth refused by peer");
+    client.set_authenticated();
+
+    if on_demand {
+        client.rekey().await?;
+    }
 
+    // The message is queued during the key re-exchange, and flushed once it completes.
     client
         .send(&ChannelOpen {
             sender_channel: 0,
@@ -101,6 +567,12 @@ async fn end_to_end(
         .to::<ChannelOpenConfirmation>()
         .expect("Channel open refused by peer");
 
+    let stats = client.stats();
+    assert!(stats.rekeys >= 1);
+    assert!(stats.rx_packets > 0 && stats.tx_packets > 0);
+    assert!(stats.rx_bytes > 0 && stats.tx_bytes > 0);
+    assert!(stats.keyed.is_some() && stats.received.is_some());
+
     client
         .send(&Disconnect {
             reason: ssh_packet::trans::DisconnectReason::ByApplication,
@@ -117,3 +589,547 @@ async fn end_to_end(
 
     Ok(())
 }
+
+#[async_std::test]
+async fn end_to_end_with_rekey_backpressure() -> Result<(), Box<dyn std::error::Error>> {
+    use ssh_packet::{arch::ascii, connect::ChannelData};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server().await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(stream, Client::default()).await?;
+
+    client
+        .send(&ServiceRequest {
+            service_name: ascii!("ssh-userauth"),
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<ServiceAccept>()
+        .expect("Service refused by peer");
+
+    client
+        .send(&userauth::Request {
+            username: "user".into(),
+            service_name: ascii!("?"),
+            method: ssh_packet::userauth::Method::None,
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<Success>()
+        .expect("Auth refused by peer");
+    client.set_authenticated();
+
+    client.rekey().await?;
+
+    // Without any call to `recv`, the key re-exchange is driven by `send` once too many packets are queued.
+    client
+        .send(&ChannelOpen {
+            sender_channel: 0,
+            initial_window_size: 128,
+            maximum_packet_size: 128,
+            context: ChannelOpenContext::Session,
+        })
+        .await?;
+    for _ in 0..128 {
+        client
+            .send(&ChannelData {
+                recipient_channel: 0,
+                data: b"data".as_ref().into(),
+            })
+            .await?;
+    }
+
+    assert!(handle.await?.to::<ChannelData>().is_ok());
+
+    client
+        .recv()
+        .await?
+        .to::<ChannelOpenConfirmation>()
+        .expect("Channel open refused by peer");
+    assert_eq!(client.stats().rekeys, 1);
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn end_to_end_with_rekey_across_auth() -> Result<(), Box<dyn std::error::Error>> {
+    use assh::algorithm::Compress;
+    use ssh_packet::arch::ascii;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    // The success is queued behind the key re-exchange, and the compression
+    // is to be activated only once it is written after the `SSH_MSG_NEWKEYS`.
+    let (addr, handle) = common::server_rekeying_on_auth(server::Server {
+        algorithms: server::Algorithms {
+            compressions: vec![Compress::ZlibOpenssh],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(
+        stream,
+        Client {
+            algorithms: Algorithms {
+                compressions: vec![Compress::ZlibOpenssh],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    client
+        .send(&ServiceRequest {
+            service_name: ascii!("ssh-userauth"),
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<ServiceAccept>()
+        .expect("Service refused by peer");
+
+    client
+        .send(&userauth::Request {
+            username: "user".into(),
+            service_name: ascii!("?"),
+            method: ssh_packet::userauth::Method::None,
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<Success>()
+        .expect("Auth refused by peer");
+    client.set_authenticated();
+
+    client
+        .send(&ChannelOpen {
+            sender_channel: 0,
+            initial_window_size: 128,
+            maximum_packet_size: 128,
+            context: ChannelOpenContext::Session,
+        })
+        .await?;
+    client
+        .recv()
+        .await?
+        .to::<ChannelOpenConfirmation>()
+        .expect("Channel open refused by peer");
+
+    assert_eq!(client.stats().rekeys, 1);
+    assert_eq!(
+        client
+            .negociated()
+            .expect("The key-exchange has not been completed")
+            .server_to_client
+            .compress,
+        Compress::ZlibOpenssh
+    );
+
+    client
+        .send(&Disconnect {
+            reason: ssh_packet::trans::DisconnectReason::ByApplication,
+            description: "bbbb".into(),
+            language: Default::default(),
+        })
+        .await?;
+
+    assert!(matches!(handle.await, Err(Error::Disconnected(_))));
+
+    Ok(())
+}
+
+/// A bare client speaking the binary protocol without encryption, to craft arbitrary exchanges.
+mod raw {
+    use async_std::net::TcpStream;
+    use futures::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, io::BufReader};
+    use ssh_packet::{
+        IntoPacket, Packet,
+        arch::{NameList, id::Id},
+        trans::KexInit,
+    };
+
+    pub struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        pub async fn connect(addr: std::net::SocketAddr) -> std::io::Result<Self> {
+            let stream = TcpStream::connect(addr).await?;
+            let (mut reader, mut writer) = (BufReader::new(stream.clone()), stream);
+
+            Id::v2("client", None::<&str>)
+                .to_writer(&mut writer)
+                .await?;
+
+            let mut id = String::new();
+            while !id.starts_with("SSH-") {
+                id.clear();
+                reader.read_line(&mut id).await?;
+            }
+
+            Ok(Self { reader, writer })
+        }
+
+        pub fn kexinit(kexs: &[&str], first_kex_packet_follows: bool) -> KexInit<'static> {
+            KexInit {
+                cookie: Default::default(),
+                kex_algorithms: NameList::from_iter(kexs),
+                server_host_key_algorithms: NameList::from_iter(["ssh-ed25519"]),
+                encryption_algorithms_client_to_server: NameList::from_iter(["aes128-ctr"]),
+                encryption_algorithms_server_to_client: NameList::from_iter(["aes128-ctr"]),
+                mac_algorithms_client_to_server: NameList::from_iter(["hmac-sha2-256"]),
+                mac_algorithms_server_to_client: NameList::from_iter(["hmac-sha2-256"]),
+                compression_algorithms_client_to_server: NameList::from_iter(["none"]),
+                compression_algorithms_server_to_client: NameList::from_iter(["none"]),
+                languages_client_to_server: Default::default(),
+                languages_server_to_client: Default::default(),
+                first_kex_packet_follows: first_kex_packet_follows.into(),
+            }
+        }
+
+        pub async fn send(&mut self, message: impl IntoPacket) -> std::io::Result<()> {
+            let payload = message.into_packet();
+
+            let mut padding = 8 - (5 + payload.len()) % 8;
+            if padding < 4 {
+                padding += 8;
+            }
+
+            self.writer
+                .write_all(&((1 + payload.len() + padding) as u32).to_be_bytes())
+                .await?;
+            self.writer.write_all(&[padding as u8]).await?;
+            self.writer.write_all(&payload).await?;
+            self.writer.write_all(&vec![0; padding]).await
+        }
+
+        pub async fn recv(&mut self) -> std::io::Result<Packet> {
+            let mut length = [0; 4];
+            self.reader.read_exact(&mut length).await?;
+
+            let mut data = vec![0; u32::from_be_bytes(length) as usize];
+            self.reader.read_exact(&mut data).await?;
+
+            let padding = data[0] as usize;
+
+            Ok(Packet(data[1..data.len() - padding].to_vec()))
+        }
+
+        pub fn shutdown(self) -> std::io::Result<()> {
+            self.writer.shutdown(std::net::Shutdown::Both)
+        }
+    }
+}
+
+#[async_std::test]
+async fn strict_kex_rejects_packets_before_kexinit() -> Result<(), Box<dyn std::error::Error>> {
+    use ssh_packet::trans::Ignore;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server().await?;
+    let mut client = raw::Client::connect(addr).await?;
+
+    // Inject an `SSH_MSG_IGNORE` before the `SSH_MSG_KEXINIT`, shifting sequence numbers.
+    client
+        .send(&Ignore {
+            data: vec![0; 8].into(),
+        })
+        .await?;
+    client
+        .send(&raw::Client::kexinit(
+            &["curve25519-sha256", "kex-strict-c-v00@openssh.com"],
+            false,
+        ))
+        .await?;
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(matches!(
+        message,
+        Err(Error::Disconnected(DisconnectedError {
+            reason: DisconnectReason::ProtocolError,
+            ..
+        }))
+    ));
+
+    Ok(())
+}
+
+#[rstest]
+#[case::right_guess(true)]
+#[case::wrong_guess(false)]
+#[async_std::test]
+async fn kex_with_first_packet_guess(
+    #[case] right: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use ssh_packet::trans::{KexEcdhInit, KexEcdhReply, KexInit};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    // The server prefers `curve25519-sha256` only in the right guess case.
+    let mut algorithms = assh::side::server::Algorithms::default();
+    if right {
+        algorithms.kexs = vec!["curve25519-sha256".parse()?];
+    }
+
+    let (addr, handle) = common::server_with(assh::side::server::Server {
+        algorithms,
+        ..Default::default()
+    })
+    .await?;
+    let mut client = raw::Client::connect(addr).await?;
+
+    client
+        .send(&raw::Client::kexinit(&["curve25519-sha256"], true))
+        .await?;
+
+    // On a wrong guess, the guessed packet is garbage which must be discarded by the server.
+    if !right {
+        client
+            .send(&KexEcdhInit {
+                q_c: vec![0; 3].into(),
+            })
+            .await?;
+    }
+    client
+        .send(&KexEcdhInit {
+            q_c: vec![9; 32].into(),
+        })
+        .await?;
+
+    client.recv().await?.to::<KexInit>()?;
+    client
+        .recv()
+        .await?
+        .to::<KexEcdhReply>()
+        .expect("The server didn't reply to the key-exchange");
+    client.shutdown()?;
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(message.is_err());
+
+    Ok(())
+}
+
+#[rstest]
+#[case::ignore(true)]
+#[case::channel_open(false)]
+#[async_std::test]
+async fn kex_with_interleaved_message(
+    #[case] allowed: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use ssh_packet::trans::{Ignore, KexEcdhInit, KexEcdhReply, KexInit};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server().await?;
+    let mut client = raw::Client::connect(addr).await?;
+
+    client
+        .send(&raw::Client::kexinit(&["curve25519-sha256"], false))
+        .await?;
+
+    // Only the generic transport messages are allowed in the middle of the key-exchange.
+    if allowed {
+        client
+            .send(&Ignore {
+                data: vec![0; 8].into(),
+            })
+            .await?;
+    } else {
+        client
+            .send(&ChannelOpen {
+                sender_channel: 0,
+                initial_window_size: 128,
+                maximum_packet_size: 128,
+                context: ChannelOpenContext::Session,
+            })
+            .await?;
+    }
+
+    client
+        .send(&KexEcdhInit {
+            q_c: vec![9; 32].into(),
+        })
+        .await?;
+
+    client.recv().await?.to::<KexInit>()?;
+    let reply = client.recv().await?;
+    client.shutdown()?;
+
+    assert_eq!(reply.to::<KexEcdhReply>().is_ok(), allowed);
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    if allowed {
+        assert!(message.is_err());
+    } else {
+        assert!(matches!(
+            message,
+            Err(Error::Disconnected(DisconnectedError {
+                reason: DisconnectReason::ProtocolError,
+                ..
+            }))
+        ));
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case::id(false)]
+#[case::kex(true)]
+#[async_std::test]
+async fn timeout_before_kex(#[case] identified: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use assh::side::server::{Server, Timeouts};
+    use ssh_packet::trans::KexInit;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server_with(Server {
+        timeouts: Timeouts {
+            id: Some(Duration::from_millis(200)),
+            kex: Some(Duration::from_millis(200)),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    // The client stalls before sending either it's identification or it's `SSH_MSG_KEXINIT`.
+    let _stalled = if identified {
+        let mut client = raw::Client::connect(addr).await?;
+
+        client.recv().await?.to::<KexInit>()?;
+        let disconnect = client.recv().await?.to::<Disconnect>()?;
+        assert!(matches!(
+            disconnect.reason,
+            DisconnectReason::ConnectionLost
+        ));
+
+        (Some(client), None)
+    } else {
+        (None, Some(TcpStream::connect(addr).await?))
+    };
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(matches!(
+        message,
+        Err(Error::Disconnected(DisconnectedError {
+            by: DisconnectedBy::Us,
+            reason: DisconnectReason::ConnectionLost,
+            ..
+        }))
+    ));
+
+    Ok(())
+}
+
+#[rstest]
+#[case::auth(
+    Some(Duration::from_millis(200)),
+    None,
+    DisconnectReason::ByApplication
+)]
+#[case::idle(
+    None,
+    Some(Duration::from_millis(200)),
+    DisconnectReason::ConnectionLost
+)]
+#[async_std::test]
+async fn timeout_after_kex(
+    #[case] auth: Option<Duration>,
+    #[case] idle: Option<Duration>,
+    #[case] reason: DisconnectReason,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use assh::side::server::{Server, Timeouts};
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init()
+        .ok();
+
+    let (addr, handle) = common::server_with(Server {
+        timeouts: Timeouts {
+            auth,
+            idle,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await?;
+
+    let stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut client = Session::new(stream, Client::default()).await?;
+
+    // The client completes the key-exchange, but never requests any service.
+    let result = client.recv().await;
+
+    tracing::info!("result: {result:?}");
+
+    assert!(matches!(
+        result,
+        Err(Error::Disconnected(DisconnectedError {
+            by: DisconnectedBy::Them,
+            reason: ref received,
+            ..
+        })) if std::mem::discriminant(received) == std::mem::discriminant(&reason)
+    ));
+
+    let message = handle.await;
+
+    tracing::info!("message: {message:?}");
+
+    assert!(matches!(
+        message,
+        Err(Error::Disconnected(DisconnectedError {
+            by: DisconnectedBy::Us,
+            ..
+        }))
+    ));
+
+    Ok(())
+}